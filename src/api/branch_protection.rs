@@ -18,7 +18,7 @@ impl Client {
             format!("repos/{repo_owner}/{repo_name}/branches/{branch_name}/protection").as_str(),
         )?;
 
-        let response = self.client.get(url).send()?.error_for_status()?;
+        let response = self.get(url)?;
 
         if response.status() == StatusCode::NOT_FOUND {
             info!(
@@ -27,7 +27,7 @@ impl Client {
             return Ok(None);
         }
 
-        Ok(Some(response.json()?))
+        Ok(Some(response.error_for_status()?.json()?))
     }
 
     pub fn update_branch_protection(
@@ -50,4 +50,49 @@ impl Client {
 
         Ok(response)
     }
+
+    pub fn delete_branch_protection(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        branch: &str,
+    ) -> Result<()> {
+        let url = self.api_root.join(
+            format!("repos/{repo_owner}/{repo_name}/branches/{branch}/protection").as_str(),
+        )?;
+
+        info!("Deleting branch protection at url '{}'", url);
+
+        self.client.delete(url).send()?.error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Enables or disables required commit signatures on a protected branch
+    pub fn set_required_signatures(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        branch: &str,
+        enabled: bool,
+    ) -> Result<()> {
+        let url = self.api_root.join(
+            format!(
+                "repos/{repo_owner}/{repo_name}/branches/{branch}/protection/required_signatures"
+            )
+            .as_str(),
+        )?;
+
+        info!("Setting required signatures to {enabled} at url '{}'", url);
+
+        let request = if enabled {
+            self.client.post(url)
+        } else {
+            self.client.delete(url)
+        };
+
+        request.send()?.error_for_status()?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,44 @@
+use anyhow::Result;
+use serde_json::json;
+use tracing::info;
+
+use crate::api::Client;
+use crate::models::Branch;
+
+impl Client {
+    pub fn get_branches(&self, repo_owner: &str, repo_name: &str) -> Result<Vec<Branch>> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/branches").as_str())?;
+
+        self.get_all_pages(url)
+    }
+
+    /// Renames a branch. If the branch is the default branch, GitHub also updates the
+    /// default branch, branch protection rules and the base branch of open pull requests.
+    pub fn rename_branch(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        branch: &str,
+        new_name: &str,
+    ) -> Result<()> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/branches/{branch}/rename").as_str())?;
+
+        info!("Renaming {repo_owner}/{repo_name} branch {branch} to {new_name}");
+
+        let body = json!({
+            "new_name": new_name,
+        });
+
+        self.client
+            .post(url)
+            .json(&body)
+            .send()?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use std::fs;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use reqwest::blocking::Response;
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::api::Client;
+
+/// The cached responses can contain data of private repositories, so only the user can read them
+fn create_private_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+
+    Ok(())
+}
+
+/// Writes to a temporary file that is renamed into place, so an interrupted write never leaves a
+/// truncated file behind
+fn write_private_file(path: &Path, contents: &str) -> Result<()> {
+    let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+
+    let write = || -> Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut file = options.open(&temp_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)?;
+
+        Ok(())
+    };
+
+    write().inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
+
+/// Responses stored on disk, to send conditional requests for them
+pub struct Cache {
+    dir: PathBuf,
+    expiry: TimeDelta,
+    /// Different tokens can see different data, so they must not share cache entries
+    key_salt: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    stored_at: DateTime<Utc>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl CacheEntry {
+    fn response(&self) -> Result<Response> {
+        let mut response = http::Response::builder().status(StatusCode::OK);
+        for (name, value) in &self.headers {
+            response = response.header(name, value);
+        }
+
+        Ok(response.body(self.body.clone())?.into())
+    }
+}
+
+impl Cache {
+    /// Opens the cache in `dir`, removing entries older than `expiry`
+    pub fn new(dir: PathBuf, expiry: TimeDelta, token: &str) -> Result<Self> {
+        create_private_dir(&dir)?;
+
+        let cache = Self {
+            dir,
+            expiry,
+            key_salt: format!("{:x}", Sha256::digest(token)),
+        };
+        cache.remove_expired()?;
+
+        Ok(cache)
+    }
+
+    /// The default cache directory, following the XDG base directory specification
+    pub fn default_dir() -> Option<PathBuf> {
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+
+        Some(cache_home.join(env!("CARGO_PKG_NAME")))
+    }
+
+    fn path(&self, url: &Url) -> PathBuf {
+        let key = Sha256::digest(format!("{}{url}", self.key_salt));
+        self.dir.join(format!("{key:x}.json"))
+    }
+
+    fn is_expired(&self, entry: &CacheEntry) -> bool {
+        Utc::now() - entry.stored_at > self.expiry
+    }
+
+    fn remove_expired(&self) -> Result<()> {
+        for file in fs::read_dir(&self.dir)? {
+            let path = file?.path();
+            let expired = fs::read_to_string(&path)
+                .ok()
+                .and_then(|json| serde_json::from_str::<CacheEntry>(&json).ok())
+                .is_none_or(|entry| self.is_expired(&entry));
+            // Temporary files are only left behind by interrupted writes
+            if expired
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension == "json" || extension == "tmp")
+            {
+                debug!("Removing expired cache entry {}", path.display());
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load(&self, url: &Url) -> Option<CacheEntry> {
+        let json = fs::read_to_string(self.path(url)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&json).ok()?;
+
+        (!self.is_expired(&entry)).then_some(entry)
+    }
+
+    fn store(&self, url: &Url, headers: &HeaderMap, body: &[u8]) -> Result<()> {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+
+        let entry = CacheEntry {
+            stored_at: Utc::now(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            headers: headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            body: String::from_utf8(body.to_vec())?,
+        };
+
+        write_private_file(&self.path(url), &serde_json::to_string(&entry)?)?;
+
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Sends a GET request. With a cache, the request is conditional and the cached body is
+    /// reused if GitHub responds with 304 Not Modified, which doesn't count against the rate limit
+    pub(super) fn get(&self, url: Url) -> Result<Response> {
+        let Some(cache) = &self.cache else {
+            return Ok(self.client.get(url).send()?);
+        };
+
+        let entry = cache.load(&url);
+
+        let mut request = self.client.get(url.clone());
+        if let Some(entry) = &entry {
+            if let Some(etag) = &entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            } else if let Some(last_modified) = &entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.send()?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(entry) = entry {
+                debug!("Using cached response for {url}");
+                return entry.response();
+            }
+        }
+
+        let cacheable = response.status() == StatusCode::OK
+            && (response.headers().contains_key(ETAG)
+                || response.headers().contains_key(LAST_MODIFIED));
+        if !cacheable {
+            return Ok(response);
+        }
+
+        let headers = response.headers().clone();
+        let body = response.bytes()?;
+
+        // Failing to cache a response shouldn't fail the request
+        if let Err(e) = cache.store(&url, &headers, &body) {
+            warn!("Failed to cache the response for {url}: {e}");
+        }
+
+        let mut response = http::Response::builder().status(StatusCode::OK);
+        if let Some(response_headers) = response.headers_mut() {
+            *response_headers = headers;
+        }
+
+        Ok(response.body(body.to_vec())?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::{Matcher, Server};
+
+    use super::*;
+
+    #[test]
+    fn test_conditional_request() -> Result<()> {
+        let mut server = Server::new();
+        let dir =
+            std::env::temp_dir().join(format!("github-repo-lint-test-{}", std::process::id()));
+        let client = crate::api::new(reqwest::blocking::Client::new(), &server.url())?
+            .with_cache(Cache::new(dir.clone(), TimeDelta::days(1), "token")?);
+        let url = Url::parse(&server.url())?.join("repos/pajlada/pajbot")?;
+
+        let first = server
+            .mock("GET", "/repos/pajlada/pajbot")
+            .match_header("if-none-match", Matcher::Missing)
+            .with_header("etag", "\"abc\"")
+            .with_header("link", "<https://api.github.com/next>; rel=\"next\"")
+            .with_body(r#"{"name": "pajbot"}"#)
+            .create();
+        let response = client.get(url.clone())?;
+        assert_eq!(response.text()?, r#"{"name": "pajbot"}"#);
+        first.assert();
+
+        let second = server
+            .mock("GET", "/repos/pajlada/pajbot")
+            .match_header("if-none-match", "\"abc\"")
+            .with_status(304)
+            .create();
+        let response = client.get(url)?;
+        second.assert();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key("link"));
+        assert_eq!(response.text()?, r#"{"name": "pajbot"}"#);
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_store() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!(
+            "github-repo-lint-test-store-{}",
+            std::process::id()
+        ));
+        let cache = Cache::new(dir.clone(), TimeDelta::days(1), "token")?;
+        let url = Url::parse("https://api.github.com/repos/pajlada/pajbot")?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, "\"abc\"".parse()?);
+        cache.store(&url, &headers, br#"{"name": "pajbot"}"#)?;
+
+        assert_eq!(cache.load(&url).unwrap().etag.as_deref(), Some("\"abc\""));
+        // Only the cache entry is left, without a temporary file
+        assert_eq!(fs::read_dir(&dir)?.count(), 1);
+        #[cfg(unix)]
+        {
+            let mode = |path: &Path| -> Result<u32> {
+                Ok(fs::metadata(path)?.permissions().mode() & 0o777)
+            };
+            assert_eq!(mode(&dir)?, 0o700);
+            assert_eq!(mode(&cache.path(&url))?, 0o600);
+        }
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+}
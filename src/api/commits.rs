@@ -0,0 +1,102 @@
+use anyhow::Result;
+
+use crate::api::Client;
+use crate::models::{CheckRun, CheckRuns, CombinedCommitStatus, Commit, CommitStatus};
+
+impl Client {
+    /// Returns the most recent commits on the given branch, newest first
+    pub fn get_recent_commits(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        branch: &str,
+        count: usize,
+    ) -> Result<Vec<Commit>> {
+        let mut url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/commits").as_str())?;
+        url.query_pairs_mut()
+            .append_pair("sha", branch)
+            .append_pair("per_page", count.to_string().as_str());
+
+        let response = self.get(url)?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+
+    pub fn get_commit_statuses(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        sha: &str,
+    ) -> Result<Vec<CommitStatus>> {
+        let mut url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/commits/{sha}/status").as_str())?;
+        url.query_pairs_mut().append_pair("per_page", "100");
+
+        self.get_all_wrapped_pages(url, |combined_status: CombinedCommitStatus| {
+            combined_status.statuses
+        })
+    }
+
+    pub fn get_check_runs(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        sha: &str,
+    ) -> Result<Vec<CheckRun>> {
+        let mut url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/commits/{sha}/check-runs").as_str())?;
+        url.query_pairs_mut().append_pair("per_page", "100");
+
+        self.get_all_wrapped_pages(url, |check_runs: CheckRuns| check_runs.check_runs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::{Matcher, Server};
+
+    use super::*;
+
+    #[test]
+    fn test_get_check_runs() -> Result<()> {
+        let mut server = Server::new();
+        let client = crate::api::new(reqwest::blocking::Client::new(), &server.url())?;
+        let path = "/repos/pajlada/pajbot/commits/abc/check-runs";
+
+        let first_page = server
+            .mock("GET", path)
+            .match_query(Matcher::Exact("per_page=100".into()))
+            .with_header(
+                "link",
+                &format!(
+                    r#"<{}{path}?per_page=100&page=2>; rel="next""#,
+                    server.url()
+                ),
+            )
+            .with_body(r#"{"total_count": 2, "check_runs": [{"name": "build", "app": {"id": 1}}]}"#)
+            .create();
+        let second_page = server
+            .mock("GET", path)
+            .match_query(Matcher::Exact("per_page=100&page=2".into()))
+            .with_body(r#"{"total_count": 2, "check_runs": [{"name": "test", "app": {"id": 2}}]}"#)
+            .create();
+
+        let check_runs = client.get_check_runs("pajlada", "pajbot", "abc")?;
+
+        first_page.assert();
+        second_page.assert();
+        assert_eq!(
+            check_runs
+                .iter()
+                .map(|check_run| check_run.name.as_str())
+                .collect::<Vec<_>>(),
+            ["build", "test"]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use reqwest::StatusCode;
+use tracing::debug;
+
+use crate::api::Client;
+use crate::models::FileContents;
+
+impl Client {
+    /// Returns the git blob SHA of the file on the given ref, which works for files of any size
+    /// or encoding since the contents are not decoded
+    pub fn get_file_sha(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        path: &str,
+        git_ref: &str,
+    ) -> Result<Option<String>> {
+        let mut url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/contents/{path}").as_str())?;
+        url.query_pairs_mut().append_pair("ref", git_ref);
+
+        let response = self.get(url)?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            debug!("{repo_owner}/{repo_name} does not have {path} on {git_ref}");
+            return Ok(None);
+        }
+
+        let contents: FileContents = response.error_for_status()?.json()?;
+
+        Ok(Some(contents.sha))
+    }
+
+    /// Whether the path exists on the given ref. Unlike `get_file_sha`, this works for
+    /// directories too
+    pub fn file_exists(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        path: &str,
+        git_ref: &str,
+    ) -> Result<bool> {
+        let mut url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/contents/{path}").as_str())?;
+        url.query_pairs_mut().append_pair("ref", git_ref);
+
+        let response = self.get(url)?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            debug!("{repo_owner}/{repo_name} does not have {path} on {git_ref}");
+            return Ok(false);
+        }
+
+        response.error_for_status()?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::{Matcher, Server};
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn test_get_file_sha_of_large_file() -> Result<()> {
+        let mut server = Server::new();
+        let client = crate::api::new(reqwest::blocking::Client::new(), &server.url())?;
+
+        // Files over 1 MB have no content
+        let mock = server
+            .mock("GET", "/repos/pajlada/pajbot/contents/logo.png")
+            .match_query(Matcher::Exact("ref=master".into()))
+            .with_body(r#"{"type": "file", "sha": "abc", "encoding": "none", "content": ""}"#)
+            .create();
+
+        assert_eq!(
+            client.get_file_sha("pajlada", "pajbot", "logo.png", "master")?,
+            Some("abc".to_string())
+        );
+        mock.assert();
+
+        Ok(())
+    }
+
+    #[rstest]
+    // Files over 1 MB have no content
+    #[case(200, r#"{"type": "file", "encoding": "none", "content": ""}"#, true)]
+    #[case(200, r#"[{"type": "file", "name": "CODEOWNERS"}]"#, true)]
+    #[case(404, r#"{"message": "Not Found"}"#, false)]
+    fn test_file_exists(
+        #[case] status: usize,
+        #[case] body: &str,
+        #[case] expected: bool,
+    ) -> Result<()> {
+        let mut server = Server::new();
+        let client = crate::api::new(reqwest::blocking::Client::new(), &server.url())?;
+
+        let mock = server
+            .mock("GET", "/repos/pajlada/pajbot/contents/docs")
+            .match_query(Matcher::Exact("ref=master".into()))
+            .with_status(status)
+            .with_body(body)
+            .create();
+
+        assert_eq!(
+            client.file_exists("pajlada", "pajbot", "docs", "master")?,
+            expected
+        );
+        mock.assert();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use serde_json::json;
+use tracing::info;
+
+use crate::api::Client;
+use crate::models::{CustomProperty, CustomPropertyAssignment};
+
+impl Client {
+    pub fn get_organization_custom_properties(&self, org: &str) -> Result<Vec<CustomProperty>> {
+        let url = self
+            .api_root
+            .join(format!("orgs/{org}/properties/schema").as_str())?;
+
+        let response = self.get(url)?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+
+    pub fn get_repository_custom_properties(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+    ) -> Result<Vec<CustomPropertyAssignment>> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/properties/values").as_str())?;
+
+        let response = self.get(url)?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+
+    pub fn update_repository_custom_properties(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        properties: &[CustomPropertyAssignment],
+    ) -> Result<()> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/properties/values").as_str())?;
+
+        info!("Updating custom properties of {repo_owner}/{repo_name}: {properties:?}");
+
+        self.client
+            .patch(url)
+            .json(&json!({ "properties": properties }))
+            .send()?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use tracing::info;
+
+use crate::api::Client;
+use crate::models::DeployKey;
+
+impl Client {
+    pub fn get_deploy_keys(&self, repo_owner: &str, repo_name: &str) -> Result<Vec<DeployKey>> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/keys").as_str())?;
+
+        self.get_all_pages(url)
+    }
+
+    pub fn delete_deploy_key(&self, repo_owner: &str, repo_name: &str, key_id: i64) -> Result<()> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/keys/{key_id}").as_str())?;
+
+        info!("Deleting deploy key {key_id} from {repo_owner}/{repo_name}");
+
+        self.client.delete(url).send()?.error_for_status()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::info;
+
+use crate::api::Client;
+use crate::models::{
+    DeploymentBranchPolicies, DeploymentBranchPolicy, Environment, EnvironmentUpdate,
+};
+
+#[derive(Deserialize)]
+struct User {
+    id: i64,
+}
+
+impl Client {
+    pub fn get_environment(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        environment: &str,
+    ) -> Result<Option<Environment>> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/environments/{environment}").as_str())?;
+
+        let response = self.get(url)?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        Ok(Some(response.error_for_status()?.json()?))
+    }
+
+    /// Creates the environment, or replaces its protection rules if it already exists
+    pub fn update_environment(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        environment: &str,
+        update: &EnvironmentUpdate,
+    ) -> Result<()> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/environments/{environment}").as_str())?;
+
+        info!("Updating environment {environment} on {repo_owner}/{repo_name}: {update:?}");
+
+        self.client
+            .put(url)
+            .json(update)
+            .send()?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    pub fn get_deployment_branch_policies(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        environment: &str,
+    ) -> Result<Vec<DeploymentBranchPolicy>> {
+        let mut url = self.api_root.join(
+            format!(
+                "repos/{repo_owner}/{repo_name}/environments/{environment}/deployment-branch-policies"
+            )
+            .as_str(),
+        )?;
+        url.query_pairs_mut().append_pair("per_page", "100");
+
+        self.get_all_wrapped_pages(url, |policies: DeploymentBranchPolicies| {
+            policies.branch_policies
+        })
+    }
+
+    pub fn create_deployment_branch_policy(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        environment: &str,
+        branch_pattern: &str,
+    ) -> Result<()> {
+        let url = self.api_root.join(
+            format!(
+                "repos/{repo_owner}/{repo_name}/environments/{environment}/deployment-branch-policies"
+            )
+            .as_str(),
+        )?;
+
+        let body = json!({
+            "name": branch_pattern,
+            "type": "branch",
+        });
+
+        self.client
+            .post(url)
+            .json(&body)
+            .send()?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    pub fn delete_deployment_branch_policy(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        environment: &str,
+        policy_id: i64,
+    ) -> Result<()> {
+        let url = self.api_root.join(
+            format!(
+                "repos/{repo_owner}/{repo_name}/environments/{environment}/deployment-branch-policies/{policy_id}"
+            )
+            .as_str(),
+        )?;
+
+        self.client.delete(url).send()?.error_for_status()?;
+
+        Ok(())
+    }
+
+    pub fn get_user_id(&self, login: &str) -> Result<i64> {
+        let url = self.api_root.join(format!("users/{login}").as_str())?;
+
+        let user: User = self.get(url)?.error_for_status()?.json()?;
+
+        Ok(user.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::{Matcher, Server};
+
+    use super::*;
+
+    #[test]
+    fn test_get_deployment_branch_policies() -> Result<()> {
+        let mut server = Server::new();
+        let client = crate::api::new(reqwest::blocking::Client::new(), &server.url())?;
+        let path = "/repos/pajlada/pajbot/environments/production/deployment-branch-policies";
+
+        let first_page = server
+            .mock("GET", path)
+            .match_query(Matcher::Exact("per_page=100".into()))
+            .with_header(
+                "link",
+                &format!(
+                    r#"<{}{path}?per_page=100&page=2>; rel="next""#,
+                    server.url()
+                ),
+            )
+            .with_body(r#"{"total_count": 2, "branch_policies": [{"id": 1, "name": "master"}]}"#)
+            .create();
+        let second_page = server
+            .mock("GET", path)
+            .match_query(Matcher::Exact("per_page=100&page=2".into()))
+            .with_body(r#"{"total_count": 2, "branch_policies": [{"id": 2, "name": "release/*"}]}"#)
+            .create();
+
+        let policies = client.get_deployment_branch_policies("pajlada", "pajbot", "production")?;
+
+        first_page.assert();
+        second_page.assert();
+        assert_eq!(
+            policies
+                .iter()
+                .map(|policy| policy.name.as_str())
+                .collect::<Vec<_>>(),
+            ["master", "release/*"]
+        );
+
+        Ok(())
+    }
+}
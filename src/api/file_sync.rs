@@ -0,0 +1,444 @@
+use anyhow::Result;
+use tracing::info;
+
+use crate::api::Client;
+use crate::file_sync::{git_blob_sha, FileChange, FileSync, FileSyncResult, SyncedFile};
+use crate::models::GitTreeEntry;
+
+impl Client {
+    /// Returns the files whose contents on `git_ref` differ from their canonical contents
+    pub fn diff_files<'a>(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        git_ref: &str,
+        files: &'a [SyncedFile],
+    ) -> Result<Vec<FileChange<'a>>> {
+        let mut changes = Vec::new();
+
+        for file in files {
+            let desired = file.desired_content()?;
+            let actual = self.get_file_sha(repo_owner, repo_name, &file.path, git_ref)?;
+
+            if actual != Some(git_blob_sha(&desired)) {
+                changes.push(FileChange {
+                    path: &file.path,
+                    content: desired,
+                    exists: actual.is_some(),
+                });
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Pushes the changed files to the sync branch and opens a pull request against `base_branch`.
+    /// If a pull request from the sync branch is already open, it is updated instead.
+    pub fn sync_files(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        base_branch: &str,
+        file_sync: &FileSync,
+        changes: &[FileChange],
+    ) -> Result<FileSyncResult> {
+        let branch = file_sync.branch.as_str();
+
+        let existing_branch = self.get_branch_ref(repo_owner, repo_name, branch)?;
+
+        let mut branch_up_to_date = existing_branch.is_some();
+        if existing_branch.is_some() {
+            for change in changes {
+                let actual = self.get_file_sha(repo_owner, repo_name, change.path, branch)?;
+                if actual != Some(git_blob_sha(&change.content)) {
+                    branch_up_to_date = false;
+                    break;
+                }
+            }
+        }
+
+        if !branch_up_to_date {
+            let base = self
+                .get_branch_ref(repo_owner, repo_name, base_branch)?
+                .ok_or_else(|| {
+                    anyhow::anyhow!("{repo_owner}/{repo_name} has no branch named {base_branch}")
+                })?;
+            let base_commit = self.get_git_commit(repo_owner, repo_name, &base.object.sha)?;
+
+            let blobs = changes
+                .iter()
+                .map(|change| self.create_git_blob(repo_owner, repo_name, &change.content))
+                .collect::<Result<Vec<_>>>()?;
+            let entries: Vec<GitTreeEntry> = changes
+                .iter()
+                .zip(&blobs)
+                .map(|(change, blob)| GitTreeEntry::file(change.path, &blob.sha))
+                .collect();
+            let tree =
+                self.create_git_tree(repo_owner, repo_name, &base_commit.tree.sha, &entries)?;
+            let commit = self.create_git_commit(
+                repo_owner,
+                repo_name,
+                &file_sync.commit_message,
+                &tree.sha,
+                &base.object.sha,
+            )?;
+
+            info!(
+                "{repo_owner}/{repo_name}: pushing {} to {branch}",
+                commit.sha
+            );
+
+            if existing_branch.is_some() {
+                self.force_update_branch_ref(repo_owner, repo_name, branch, &commit.sha)?;
+            } else {
+                self.create_branch_ref(repo_owner, repo_name, branch, &commit.sha)?;
+            }
+        }
+
+        match self.find_open_pull_request(repo_owner, repo_name, branch)? {
+            Some(pull_request) if branch_up_to_date => Ok(FileSyncResult::UpToDate(pull_request)),
+            Some(pull_request) => Ok(FileSyncResult::Updated(self.update_pull_request(
+                repo_owner,
+                repo_name,
+                pull_request.number,
+                &file_sync.pull_request_title,
+                &file_sync.pull_request_body,
+            )?)),
+            None => Ok(FileSyncResult::Opened(self.create_pull_request(
+                repo_owner,
+                repo_name,
+                branch,
+                base_branch,
+                &file_sync.pull_request_title,
+                &file_sync.pull_request_body,
+            )?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::{Matcher, Server};
+    use serde_json::json;
+
+    use super::*;
+
+    const PULL_REQUEST: &str = r#"{
+        "number": 7,
+        "html_url": "https://github.com/pajlada/test/pull/7",
+        "title": "Sync files with github-repo-lint",
+        "head": {"ref": "github-repo-lint/file-sync", "sha": "c0ffee"}
+    }"#;
+
+    fn file_sync() -> FileSync {
+        FileSync {
+            branch: "github-repo-lint/file-sync".to_string(),
+            commit_message: "Sync files with github-repo-lint".to_string(),
+            pull_request_title: "Sync files with github-repo-lint".to_string(),
+            pull_request_body: String::new(),
+            files: vec![SyncedFile {
+                path: ".github/dependabot.yml".to_string(),
+                content: Some("version: 2\n".to_string()),
+                source: None,
+            }],
+        }
+    }
+
+    fn contents_body(content: &str) -> String {
+        json!({
+            "path": ".github/dependabot.yml",
+            "sha": git_blob_sha(content.as_bytes()),
+            "encoding": "base64",
+        })
+        .to_string()
+    }
+
+    fn client(server: &Server) -> Result<Client> {
+        crate::api::new(reqwest::blocking::Client::new(), &server.url())
+    }
+
+    #[test]
+    fn test_diff_files() -> Result<()> {
+        let mut server = Server::new();
+        let client = client(&server)?;
+        let file_sync = file_sync();
+
+        let mock = server
+            .mock("GET", "/repos/pajlada/test/contents/.github/dependabot.yml")
+            .match_query(Matcher::UrlEncoded("ref".into(), "main".into()))
+            .with_body(contents_body("version: 1\n"))
+            .create();
+
+        let changes = client.diff_files("pajlada", "test", "main", &file_sync.files)?;
+        mock.assert();
+        assert_eq!(
+            changes,
+            vec![FileChange {
+                path: ".github/dependabot.yml",
+                content: b"version: 2\n".to_vec(),
+                exists: true,
+            }]
+        );
+
+        let mock = server
+            .mock("GET", "/repos/pajlada/test/contents/.github/dependabot.yml")
+            .match_query(Matcher::UrlEncoded("ref".into(), "main".into()))
+            .with_body(contents_body("version: 2\n"))
+            .create();
+
+        let changes = client.diff_files("pajlada", "test", "main", &file_sync.files)?;
+        mock.assert();
+        assert!(changes.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_files_opens_pull_request() -> Result<()> {
+        let mut server = Server::new();
+        let client = client(&server)?;
+        let file_sync = file_sync();
+        let changes = [FileChange {
+            path: ".github/dependabot.yml",
+            content: b"version: 2\n".to_vec(),
+            exists: false,
+        }];
+
+        let mocks = [
+            server
+                .mock(
+                    "GET",
+                    "/repos/pajlada/test/git/ref/heads/github-repo-lint/file-sync",
+                )
+                .with_status(404)
+                .create(),
+            server
+                .mock("GET", "/repos/pajlada/test/git/ref/heads/main")
+                .with_body(r#"{"ref": "refs/heads/main", "object": {"sha": "base"}}"#)
+                .create(),
+            server
+                .mock("GET", "/repos/pajlada/test/git/commits/base")
+                .with_body(r#"{"sha": "base", "tree": {"sha": "basetree"}}"#)
+                .create(),
+            server
+                .mock("POST", "/repos/pajlada/test/git/blobs")
+                .match_body(Matcher::Json(json!({
+                    "content": "dmVyc2lvbjogMgo=",
+                    "encoding": "base64",
+                })))
+                .with_status(201)
+                .with_body(r#"{"sha": "newblob"}"#)
+                .create(),
+            server
+                .mock("POST", "/repos/pajlada/test/git/trees")
+                .match_body(Matcher::PartialJson(json!({
+                    "base_tree": "basetree",
+                    "tree": [{"path": ".github/dependabot.yml", "sha": "newblob"}],
+                })))
+                .with_body(r#"{"sha": "newtree"}"#)
+                .create(),
+            server
+                .mock("POST", "/repos/pajlada/test/git/commits")
+                .match_body(Matcher::PartialJson(
+                    json!({"tree": "newtree", "parents": ["base"]}),
+                ))
+                .with_body(r#"{"sha": "newcommit"}"#)
+                .create(),
+            server
+                .mock("POST", "/repos/pajlada/test/git/refs")
+                .match_body(Matcher::Json(json!({
+                    "ref": "refs/heads/github-repo-lint/file-sync",
+                    "sha": "newcommit",
+                })))
+                .with_status(201)
+                .with_body(
+                    r#"{"ref": "refs/heads/github-repo-lint/file-sync", "object": {"sha": "newcommit"}}"#,
+                )
+                .create(),
+            server
+                .mock("GET", "/repos/pajlada/test/pulls")
+                .match_query(Matcher::AllOf(vec![
+                    Matcher::UrlEncoded("state".into(), "open".into()),
+                    Matcher::UrlEncoded(
+                        "head".into(),
+                        "pajlada:github-repo-lint/file-sync".into(),
+                    ),
+                ]))
+                .with_body("[]")
+                .create(),
+            server
+                .mock("POST", "/repos/pajlada/test/pulls")
+                .match_body(Matcher::PartialJson(json!({
+                    "head": "github-repo-lint/file-sync",
+                    "base": "main",
+                })))
+                .with_status(201)
+                .with_body(PULL_REQUEST)
+                .create(),
+        ];
+
+        let result = client.sync_files("pajlada", "test", "main", &file_sync, &changes)?;
+
+        for mock in mocks {
+            mock.assert();
+        }
+        assert!(matches!(result, FileSyncResult::Opened(pr) if pr.number == 7));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_files_reuses_up_to_date_pull_request() -> Result<()> {
+        let mut server = Server::new();
+        let client = client(&server)?;
+        let file_sync = file_sync();
+        let changes = [FileChange {
+            path: ".github/dependabot.yml",
+            content: b"version: 2\n".to_vec(),
+            exists: true,
+        }];
+
+        let mocks = [
+            server
+                .mock(
+                    "GET",
+                    "/repos/pajlada/test/git/ref/heads/github-repo-lint/file-sync",
+                )
+                .with_body(
+                    r#"{"ref": "refs/heads/github-repo-lint/file-sync", "object": {"sha": "c0ffee"}}"#,
+                )
+                .create(),
+            server
+                .mock("GET", "/repos/pajlada/test/contents/.github/dependabot.yml")
+                .match_query(Matcher::UrlEncoded(
+                    "ref".into(),
+                    "github-repo-lint/file-sync".into(),
+                ))
+                .with_body(contents_body("version: 2\n"))
+                .create(),
+            server
+                .mock("GET", "/repos/pajlada/test/pulls")
+                .match_query(Matcher::Any)
+                .with_body(format!("[{PULL_REQUEST}]"))
+                .create(),
+        ];
+        let no_commit = server
+            .mock("POST", "/repos/pajlada/test/git/commits")
+            .expect(0)
+            .create();
+        let no_new_pull_request = server
+            .mock("POST", "/repos/pajlada/test/pulls")
+            .expect(0)
+            .create();
+
+        let result = client.sync_files("pajlada", "test", "main", &file_sync, &changes)?;
+
+        for mock in mocks {
+            mock.assert();
+        }
+        no_commit.assert();
+        no_new_pull_request.assert();
+        assert!(matches!(result, FileSyncResult::UpToDate(pr) if pr.number == 7));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_files_updates_pull_request() -> Result<()> {
+        let mut server = Server::new();
+        let client = client(&server)?;
+        let file_sync = file_sync();
+        let changes = [FileChange {
+            path: ".github/dependabot.yml",
+            content: b"version: 2\n".to_vec(),
+            exists: true,
+        }];
+
+        let mocks = [
+            server
+                .mock(
+                    "GET",
+                    "/repos/pajlada/test/git/ref/heads/github-repo-lint/file-sync",
+                )
+                .with_body(
+                    r#"{"ref": "refs/heads/github-repo-lint/file-sync", "object": {"sha": "c0ffee"}}"#,
+                )
+                .create(),
+            // The sync branch still has the contents of an earlier sync
+            server
+                .mock("GET", "/repos/pajlada/test/contents/.github/dependabot.yml")
+                .match_query(Matcher::UrlEncoded(
+                    "ref".into(),
+                    "github-repo-lint/file-sync".into(),
+                ))
+                .with_body(contents_body("version: 1\n"))
+                .create(),
+            server
+                .mock("GET", "/repos/pajlada/test/git/ref/heads/main")
+                .with_body(r#"{"ref": "refs/heads/main", "object": {"sha": "base"}}"#)
+                .create(),
+            server
+                .mock("GET", "/repos/pajlada/test/git/commits/base")
+                .with_body(r#"{"sha": "base", "tree": {"sha": "basetree"}}"#)
+                .create(),
+            server
+                .mock("POST", "/repos/pajlada/test/git/blobs")
+                .with_status(201)
+                .with_body(r#"{"sha": "newblob"}"#)
+                .create(),
+            server
+                .mock("POST", "/repos/pajlada/test/git/trees")
+                .with_body(r#"{"sha": "newtree"}"#)
+                .create(),
+            server
+                .mock("POST", "/repos/pajlada/test/git/commits")
+                .match_body(Matcher::PartialJson(
+                    json!({"tree": "newtree", "parents": ["base"]}),
+                ))
+                .with_body(r#"{"sha": "newcommit"}"#)
+                .create(),
+            server
+                .mock(
+                    "PATCH",
+                    "/repos/pajlada/test/git/refs/heads/github-repo-lint/file-sync",
+                )
+                .match_body(Matcher::Json(json!({"sha": "newcommit", "force": true})))
+                .with_body(
+                    r#"{"ref": "refs/heads/github-repo-lint/file-sync", "object": {"sha": "newcommit"}}"#,
+                )
+                .create(),
+            server
+                .mock("GET", "/repos/pajlada/test/pulls")
+                .match_query(Matcher::Any)
+                .with_body(format!("[{PULL_REQUEST}]"))
+                .create(),
+            server
+                .mock("PATCH", "/repos/pajlada/test/pulls/7")
+                .match_body(Matcher::PartialJson(
+                    json!({"title": "Sync files with github-repo-lint"}),
+                ))
+                .with_body(PULL_REQUEST)
+                .create(),
+        ];
+        let no_new_branch = server
+            .mock("POST", "/repos/pajlada/test/git/refs")
+            .expect(0)
+            .create();
+        let no_new_pull_request = server
+            .mock("POST", "/repos/pajlada/test/pulls")
+            .expect(0)
+            .create();
+
+        let result = client.sync_files("pajlada", "test", "main", &file_sync, &changes)?;
+
+        for mock in mocks {
+            mock.assert();
+        }
+        no_new_branch.assert();
+        no_new_pull_request.assert();
+        assert!(matches!(result, FileSyncResult::Updated(pr) if pr.number == 7));
+
+        Ok(())
+    }
+}
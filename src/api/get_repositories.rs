@@ -1,20 +1,20 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
-use tracing::debug;
+use tracing::warn;
 
 use crate::api::Client;
 use crate::models::repository;
 use crate::models::repository::Repository;
-use std::io::Write;
-
-use console::Term;
-
-use super::pagination::{get_pagination_data, PaginationData};
+use crate::targets::AuthenticatedUserFilter;
 
 impl Client {
     pub fn get_repository(&self, repo_full_name: &str) -> Result<Repository> {
         Ok(Repository {
             info: self.get_repository_info(repo_full_name)?,
             topics: self.get_repository_topics(repo_full_name)?,
+            custom_properties: HashMap::new(),
+            branch_protection_rules: None,
         })
     }
 
@@ -23,7 +23,7 @@ impl Client {
             .api_root
             .join(format!("repos/{repo_owner_and_name}").as_str())?;
 
-        let response = self.client.get(url).send()?;
+        let response = self.get(url)?;
 
         Ok(response.json()?)
     }
@@ -33,96 +33,177 @@ impl Client {
             .api_root
             .join(format!("repos/{repo_owner_and_name}/topics").as_str())?;
 
-        let response = self.client.get(url).send()?;
+        let response = self.get(url)?;
 
         Ok(response.json()?)
     }
 
-    pub fn get_repositories_from_user(
-        &self,
-        terminal: &mut Term,
-        repo_owner: &str,
-    ) -> Result<Vec<Repository>> {
-        let mut repos = Vec::new();
-
-        let mut pagination = PaginationData {
-            next: Some(
-                self.api_root
-                    .join(format!("users/{repo_owner}/repos").as_str())?,
-            ),
-        };
+    pub fn get_repositories_from_user(&self, repo_owner: &str) -> Result<Vec<repository::Listing>> {
+        let url = self
+            .api_root
+            .join(format!("users/{repo_owner}/repos").as_str())?;
 
-        terminal.write_all(format!("Loading repositories from user {repo_owner}").as_bytes())?;
+        self.get_all_pages(url)
+    }
 
-        while let Some(url) = pagination.next {
-            let response = self.client.get(url).send()?;
+    pub fn get_repositories_from_organization(
+        &self,
+        repo_owner: &str,
+    ) -> Result<Vec<repository::Listing>> {
+        let url = self
+            .api_root
+            .join(format!("orgs/{repo_owner}/repos").as_str())?;
 
-            // Read pagination from headers
-            pagination = get_pagination_data(response.headers())?;
+        self.get_all_pages(url)
+    }
 
-            let page_repos: Vec<repository::Listing> = response.json()?;
+    /// The repositories a team in an organization has access to
+    pub fn get_repositories_from_team(
+        &self,
+        organization: &str,
+        team_slug: &str,
+    ) -> Result<Vec<repository::Listing>> {
+        let url = self
+            .api_root
+            .join(format!("orgs/{organization}/teams/{team_slug}/repos").as_str())?;
 
-            for repo in page_repos {
-                terminal.clear_line()?;
-                terminal.write_all(
-                    format!(
-                        "Loading repositories from user {} ({})",
-                        repo_owner, repo.name
-                    )
-                    .as_bytes(),
-                )?;
+        self.get_all_pages(url)
+    }
 
-                repos.push(self.get_repository(&repo.full_name)?);
+    /// The repositories matching a GitHub search query, e.g. `org:pajlads topic:bot`.
+    /// The search API returns at most 1000 results
+    pub fn get_repositories_from_search(&self, query: &str) -> Result<Vec<repository::Listing>> {
+        let mut url = self.api_root.join("search/repositories")?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("per_page", "100");
+
+        self.get_all_wrapped_pages(url, |page: repository::SearchResults| {
+            if page.incomplete_results {
+                warn!("Search for '{query}' timed out, the results are incomplete");
             }
-        }
+            page.items
+        })
+    }
 
-        terminal.write_line("").unwrap();
+    /// The repositories the GitHub App installation the token belongs to can access
+    pub fn get_repositories_from_installation(&self) -> Result<Vec<repository::Listing>> {
+        let mut url = self.api_root.join("installation/repositories")?;
+        url.query_pairs_mut().append_pair("per_page", "100");
 
-        Ok(repos)
+        self.get_all_wrapped_pages(url, |page: repository::InstallationRepositories| {
+            page.repositories
+        })
     }
 
-    pub fn get_repositories_from_organization(
+    /// Unlike `get_repositories_from_user`, this includes private repositories and repositories
+    /// the authenticated user can access as a collaborator or organization member
+    pub fn get_repositories_from_authenticated_user(
         &self,
-        terminal: &mut Term,
-        repo_owner: &str,
-    ) -> anyhow::Result<Vec<Repository>> {
-        let mut repos = Vec::new();
-
-        let mut pagination = PaginationData {
-            next: Some(
-                self.api_root
-                    .join(format!("orgs/{repo_owner}/repos").as_str())?,
-            ),
-        };
-
-        terminal
-            .write_all(format!("Loading repositories from organization {repo_owner}").as_bytes())?;
-
-        while let Some(url) = pagination.next {
-            let response = self.client.get(url).send()?;
-
-            // Read pagination from headers
-            pagination = get_pagination_data(response.headers())?;
+        filter: &AuthenticatedUserFilter,
+    ) -> Result<Vec<repository::Listing>> {
+        let mut url = self.api_root.join("user/repos")?;
+        if !filter.affiliation.is_empty() {
+            url.query_pairs_mut()
+                .append_pair("affiliation", filter.affiliation.join(",").as_str());
+        }
+        if let Some(visibility) = &filter.visibility {
+            url.query_pairs_mut()
+                .append_pair("visibility", visibility.as_str());
+        }
 
-            let page_repos: Vec<repository::Listing> = response.json()?;
+        self.get_all_pages(url)
+    }
+}
 
-            for repo in page_repos {
-                debug!("get_repositories_from_organization repo: {:?}", repo);
-                terminal.clear_line()?;
-                terminal.write_all(
-                    format!(
-                        "Loading repositories from organization {} ({})",
-                        repo_owner, repo.name
-                    )
-                    .as_bytes(),
-                )?;
+#[cfg(test)]
+mod tests {
+    use mockito::{Matcher, Server};
+
+    use super::*;
+
+    #[test]
+    fn test_get_repositories_from_authenticated_user() -> Result<()> {
+        let mut server = Server::new();
+        let client = crate::api::new(reqwest::blocking::Client::new(), &server.url())?;
+
+        let mock = server
+            .mock("GET", "/user/repos")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("affiliation".into(), "owner,collaborator".into()),
+                Matcher::UrlEncoded("visibility".into(), "private".into()),
+            ]))
+            .with_body(
+                r#"[{
+                        "name": "secret",
+                        "full_name": "pajlada/secret",
+                        "owner": {"login": "pajlada", "type": "User"},
+                        "private": true
+                    }]"#,
+            )
+            .create();
+
+        let repos = client.get_repositories_from_authenticated_user(&AuthenticatedUserFilter {
+            affiliation: vec!["owner".to_string(), "collaborator".to_string()],
+            visibility: Some("private".to_string()),
+        })?;
+
+        mock.assert();
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].full_name, "pajlada/secret");
+
+        Ok(())
+    }
 
-                repos.push(self.get_repository(&repo.full_name)?);
-            }
-        }
+    #[test]
+    fn test_get_repositories_from_search() -> Result<()> {
+        let mut server = Server::new();
+        let client = crate::api::new(reqwest::blocking::Client::new(), &server.url())?;
 
-        terminal.write_line("").unwrap();
+        let listing = |name: &str| {
+            format!(
+                r#"{{"name": "{name}", "full_name": "pajlads/{name}", "owner": {{"login": "pajlads", "type": "Organization"}}}}"#
+            )
+        };
 
-        Ok(repos)
+        let first_page = server
+            .mock("GET", "/search/repositories")
+            .match_query(Matcher::Exact(
+                "q=org%3Apajlads+topic%3Abot&per_page=100".into(),
+            ))
+            .with_header(
+                "link",
+                &format!(
+                    r#"<{}/search/repositories?q=org%3Apajlads+topic%3Abot&page=2>; rel="next""#,
+                    server.url()
+                ),
+            )
+            .with_body(format!(
+                r#"{{"total_count": 2, "incomplete_results": false, "items": [{}]}}"#,
+                listing("pajbot")
+            ))
+            .create();
+        let second_page = server
+            .mock("GET", "/search/repositories")
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_body(format!(
+                r#"{{"total_count": 2, "incomplete_results": false, "items": [{}]}}"#,
+                listing("chatterino")
+            ))
+            .create();
+
+        let repos = client.get_repositories_from_search("org:pajlads topic:bot")?;
+
+        first_page.assert();
+        second_page.assert();
+        assert_eq!(
+            repos
+                .iter()
+                .map(|repo| repo.full_name.as_str())
+                .collect::<Vec<_>>(),
+            ["pajlads/pajbot", "pajlads/chatterino"]
+        );
+
+        Ok(())
     }
 }
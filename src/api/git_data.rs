@@ -0,0 +1,152 @@
+use anyhow::Result;
+use base64::Engine;
+use reqwest::StatusCode;
+use serde_json::json;
+use tracing::debug;
+
+use crate::api::Client;
+use crate::models::{GitCommit, GitObject, GitRef, GitTreeEntry};
+
+impl Client {
+    pub fn get_branch_ref(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        branch: &str,
+    ) -> Result<Option<GitRef>> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/git/ref/heads/{branch}").as_str())?;
+
+        let response = self.get(url)?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            debug!("{repo_owner}/{repo_name} does not have a branch named {branch}");
+            return Ok(None);
+        }
+
+        Ok(Some(response.error_for_status()?.json()?))
+    }
+
+    pub fn create_branch_ref(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        branch: &str,
+        sha: &str,
+    ) -> Result<GitRef> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/git/refs").as_str())?;
+
+        let body = json!({
+            "ref": format!("refs/heads/{branch}"),
+            "sha": sha,
+        });
+
+        let response = self.client.post(url).json(&body).send()?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+
+    pub fn force_update_branch_ref(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        branch: &str,
+        sha: &str,
+    ) -> Result<GitRef> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/git/refs/heads/{branch}").as_str())?;
+
+        let body = json!({
+            "sha": sha,
+            "force": true,
+        });
+
+        let response = self.client.patch(url).json(&body).send()?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+
+    pub fn get_git_commit(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        sha: &str,
+    ) -> Result<GitCommit> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/git/commits/{sha}").as_str())?;
+
+        let response = self.get(url)?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+
+    /// Uploads the content as base64, so binary files can be created too
+    pub fn create_git_blob(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        content: &[u8],
+    ) -> Result<GitObject> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/git/blobs").as_str())?;
+
+        let body = json!({
+            "content": base64::engine::general_purpose::STANDARD.encode(content),
+            "encoding": "base64",
+        });
+
+        let response = self.client.post(url).json(&body).send()?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+
+    pub fn create_git_tree(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        base_tree: &str,
+        entries: &[GitTreeEntry],
+    ) -> Result<GitObject> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/git/trees").as_str())?;
+
+        let body = json!({
+            "base_tree": base_tree,
+            "tree": entries,
+        });
+
+        let response = self.client.post(url).json(&body).send()?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+
+    pub fn create_git_commit(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        message: &str,
+        tree: &str,
+        parent: &str,
+    ) -> Result<GitObject> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/git/commits").as_str())?;
+
+        let body = json!({
+            "message": message,
+            "tree": tree,
+            "parents": [parent],
+        });
+
+        let response = self.client.post(url).json(&body).send()?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+}
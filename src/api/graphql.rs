@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use reqwest::Url;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::api::Client;
+use crate::models::graphql::{GraphQLRepository, GraphQLResponse};
+use crate::models::Repository;
+
+const REPOSITORY_FIELDS: &str = include_str!("../../files/repository.graphql");
+
+/// The most repositories loaded with a single query
+const REPOSITORIES_PER_QUERY: usize = 100;
+
+/// The GraphQL endpoint belonging to the REST API root.
+/// On GitHub Enterprise Server, the REST API is at `/api/v3` and the GraphQL API at `/api/graphql`
+fn graphql_url(api_root: &Url) -> Result<Url> {
+    if api_root.path().trim_end_matches('/').ends_with("/api/v3") {
+        Ok(api_root.join("../graphql")?)
+    } else {
+        Ok(api_root.join("graphql")?)
+    }
+}
+
+/// Builds a query loading the given repositories, aliased as `r0`, `r1`, ...
+fn repositories_query(full_names: &[&str]) -> Result<(String, serde_json::Value)> {
+    let mut parameters = Vec::new();
+    let mut fields = Vec::new();
+    let mut variables = serde_json::Map::new();
+
+    for (i, full_name) in full_names.iter().enumerate() {
+        let Some((owner, name)) = full_name.split_once('/') else {
+            anyhow::bail!("Invalid repository '{full_name}', expected owner/name");
+        };
+
+        parameters.push(format!("$owner{i}: String!, $name{i}: String!"));
+        fields.push(format!(
+            "  r{i}: repository(owner: $owner{i}, name: $name{i}) {{ ...RepositoryFields }}"
+        ));
+        variables.insert(format!("owner{i}"), json!(owner));
+        variables.insert(format!("name{i}"), json!(name));
+    }
+
+    let query = format!(
+        "query({}) {{\n{}\n}}\n{REPOSITORY_FIELDS}",
+        parameters.join(", "),
+        fields.join("\n")
+    );
+
+    Ok((query, variables.into()))
+}
+
+impl Client {
+    /// Loads the repositories through the GraphQL API, including their topics and branch
+    /// protection rules. Repositories the query can't fully load are loaded through the REST API
+    pub fn get_repositories_with_graphql(&self, full_names: &[&str]) -> Result<Vec<Repository>> {
+        let url = graphql_url(&self.api_root)?;
+        let mut repositories = Vec::new();
+
+        for chunk in full_names.chunks(REPOSITORIES_PER_QUERY) {
+            let (query, variables) = repositories_query(chunk)?;
+
+            info!("Loading {} repositories with GraphQL", chunk.len());
+
+            let response: GraphQLResponse<HashMap<String, Option<GraphQLRepository>>> = self
+                .client
+                .post(url.clone())
+                .json(&json!({ "query": query, "variables": variables }))
+                .send()?
+                .error_for_status()?
+                .json()?;
+
+            // Errors for single repositories (e.g. missing access to branch protection rules)
+            // only null the affected fields
+            for error in &response.errors {
+                warn!("GraphQL error: {}", error.message);
+            }
+            let Some(mut data) = response.data else {
+                anyhow::bail!(
+                    "GraphQL query failed: {}",
+                    response
+                        .errors
+                        .iter()
+                        .map(|error| error.message.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            };
+
+            for (i, full_name) in chunk.iter().enumerate() {
+                let repository = data
+                    .remove(&format!("r{i}"))
+                    .flatten()
+                    .and_then(GraphQLRepository::into_repository);
+
+                if let Some(repository) = repository {
+                    repositories.push(repository);
+                } else {
+                    info!("Loading {full_name} through the REST API");
+                    repositories.push(self.get_repository(full_name)?);
+                }
+            }
+        }
+
+        Ok(repositories)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Server;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("https://api.github.com", "https://api.github.com/graphql")]
+    #[case("https://api.github.com/", "https://api.github.com/graphql")]
+    #[case(
+        "https://github.example.com/api/v3/",
+        "https://github.example.com/api/graphql"
+    )]
+    fn test_graphql_url(#[case] api_root: &str, #[case] expected: &str) -> Result<()> {
+        assert_eq!(graphql_url(&Url::parse(api_root)?)?.as_str(), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_repositories_with_graphql() -> Result<()> {
+        let mut server = Server::new();
+        let client = crate::api::new(reqwest::blocking::Client::new(), &server.url())?;
+
+        let rule = |pattern: &str, restricts_pushes: bool| {
+            json!({
+                "pattern": pattern,
+                "isAdminEnforced": true,
+                "allowsDeletions": false,
+                "allowsForcePushes": false,
+                "blocksCreations": false,
+                "lockBranch": false,
+                "lockAllowsFetchAndMerge": false,
+                "requiresLinearHistory": true,
+                "requiresConversationResolution": false,
+                "requiresCommitSignatures": false,
+                "requiresApprovingReviews": true,
+                "requiredApprovingReviewCount": 2,
+                "dismissesStaleReviews": false,
+                "requiresCodeOwnerReviews": false,
+                "requireLastPushApproval": false,
+                "requiresStatusChecks": false,
+                "requiresStrictStatusChecks": false,
+                "requiredStatusChecks": [],
+                "restrictsPushes": restricts_pushes,
+                "restrictsReviewDismissals": false,
+                "bypassPullRequestAllowances": {"totalCount": 0},
+            })
+        };
+
+        let mock = server
+            .mock("POST", "/graphql")
+            .with_body(
+                json!({
+                    "data": {
+                        "r0": {
+                            "name": "pajbot",
+                            "nameWithOwner": "pajlada/pajbot",
+                            "owner": {"login": "pajlada", "__typename": "User"},
+                            "defaultBranchRef": {"name": "master"},
+                            "isArchived": false,
+                            "isDisabled": false,
+                            "isFork": false,
+                            "isTemplate": false,
+                            "visibility": "PUBLIC",
+                            "rebaseMergeAllowed": false,
+                            "mergeCommitAllowed": false,
+                            "squashMergeAllowed": true,
+                            "deleteBranchOnMerge": true,
+                            "autoMergeAllowed": true,
+                            "hasIssuesEnabled": true,
+                            "hasProjectsEnabled": false,
+                            "hasWikiEnabled": false,
+                            "primaryLanguage": {"name": "Python"},
+                            "repositoryTopics": {"nodes": [{"topic": {"name": "bot"}}]},
+                            "branchProtectionRules": {
+                                "pageInfo": {"hasNextPage": false},
+                                "nodes": [rule("master", false), rule("release/*", true)],
+                            },
+                        }
+                    }
+                })
+                .to_string(),
+            )
+            .create();
+
+        let repositories = client.get_repositories_with_graphql(&["pajlada/pajbot"])?;
+
+        mock.assert();
+        assert_eq!(repositories.len(), 1);
+        let repository = &repositories[0];
+        assert_eq!(repository.info.full_name, "pajlada/pajbot");
+        assert_eq!(repository.info.owner.owner_type, "User");
+        assert_eq!(repository.info.visibility.as_deref(), Some("public"));
+        assert_eq!(repository.info.language.as_deref(), Some("Python"));
+        assert!(repository.topics.names.contains("bot"));
+
+        let master = repository
+            .loaded_branch_protection_rule("master")
+            .and_then(|rule| rule.branch_protection.as_ref())
+            .unwrap();
+        assert_eq!(master.enforce_admins, Some(true));
+        assert_eq!(
+            master
+                .required_pull_request_reviews
+                .as_ref()
+                .unwrap()
+                .required_approving_review_count,
+            Some(2)
+        );
+        // Push restrictions are only returned in full by the REST API
+        assert!(repository
+            .loaded_branch_protection_rule("release/1.0")
+            .is_some_and(|rule| rule.branch_protection.is_none()));
+        assert!(repository
+            .loaded_branch_protection_rule("develop")
+            .is_none());
+
+        Ok(())
+    }
+}
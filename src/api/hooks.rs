@@ -0,0 +1,128 @@
+use anyhow::Result;
+use serde_json::json;
+use tracing::info;
+
+use crate::api::Client;
+use crate::models::{Hook, HookDelivery};
+use crate::webhooks::Webhook;
+
+impl Client {
+    pub fn get_repository_hooks(&self, repo_owner: &str, repo_name: &str) -> Result<Vec<Hook>> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/hooks").as_str())?;
+
+        self.get_all_pages(url)
+    }
+
+    pub fn create_repository_hook(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        hook: &Webhook,
+    ) -> Result<Hook> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/hooks").as_str())?;
+
+        info!("Creating hook {} on {repo_owner}/{repo_name}", hook.url);
+
+        let mut config = json!({
+            "url": hook.url,
+            "content_type": hook.content_type,
+            "insecure_ssl": "0",
+        });
+        if let Some(secret) = &hook.secret {
+            config["secret"] = json!(secret);
+        }
+
+        let body = json!({
+            "name": "web",
+            "active": hook.active,
+            "events": hook.events,
+            "config": config,
+        });
+
+        let response = self.client.post(url).json(&body).send()?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+
+    /// Updates the hook without touching its secret
+    pub fn update_repository_hook(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        hook_id: i64,
+        hook: &Webhook,
+    ) -> Result<()> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/hooks/{hook_id}").as_str())?;
+
+        info!("Updating hook {} on {repo_owner}/{repo_name}", hook.url);
+
+        let body = json!({
+            "active": hook.active,
+            "events": hook.events,
+        });
+
+        self.client
+            .patch(url)
+            .json(&body)
+            .send()?
+            .error_for_status()?;
+
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/hooks/{hook_id}/config").as_str())?;
+
+        let body = json!({
+            "content_type": hook.content_type,
+            "insecure_ssl": "0",
+        });
+
+        self.client
+            .patch(url)
+            .json(&body)
+            .send()?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    pub fn delete_repository_hook(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        hook_id: i64,
+    ) -> Result<()> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/hooks/{hook_id}").as_str())?;
+
+        info!("Deleting hook {hook_id} on {repo_owner}/{repo_name}");
+
+        self.client.delete(url).send()?.error_for_status()?;
+
+        Ok(())
+    }
+
+    pub fn get_recent_hook_deliveries(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        hook_id: i64,
+        count: usize,
+    ) -> Result<Vec<HookDelivery>> {
+        let mut url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/hooks/{hook_id}/deliveries").as_str())?;
+        url.query_pairs_mut()
+            .append_pair("per_page", count.to_string().as_str());
+
+        let response = self.get(url)?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+}
@@ -3,20 +3,47 @@ mod pagination;
 mod get_repositories;
 
 mod branch_protection;
+mod branches;
+mod cache;
+mod commits;
+mod contents;
+mod custom_properties;
+mod deploy_keys;
+mod environments;
+mod file_sync;
+mod git_data;
+mod graphql;
+mod hooks;
+mod organizations;
+mod permissions;
+mod pull_requests;
 mod update_repository_settings;
 mod update_repository_topics;
 
 use reqwest::blocking::Client as r_client;
 use reqwest::Url;
 
+pub use cache::Cache;
+
+#[allow(clippy::struct_field_names)]
 pub struct Client {
     client: r_client,
     api_root: Url,
+    cache: Option<Cache>,
 }
 
 pub fn new(client: r_client, api_root: &str) -> Result<Client, anyhow::Error> {
     Ok(Client {
         client,
         api_root: Url::parse(api_root)?,
+        cache: None,
     })
 }
+
+impl Client {
+    /// Sends GET requests conditionally, reusing the cached responses that didn't change
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::{bail, Result};
+
+use crate::api::Client;
+use crate::models::Organization;
+
+impl Client {
+    /// Fails on GitHub.com, where listing every organization would page through all of GitHub
+    pub fn ensure_enterprise_server(&self) -> Result<()> {
+        if self.api_root.host_str() == Some("api.github.com") {
+            bail!(
+                "Listing every organization is only supported on GitHub Enterprise Server, set github_api_root in the config"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// All organizations of the GitHub Enterprise Server instance, in the order they were created
+    pub fn get_all_organizations(&self) -> Result<Vec<Organization>> {
+        self.ensure_enterprise_server()?;
+
+        let mut url = self.api_root.join("organizations")?;
+        url.query_pairs_mut().append_pair("per_page", "100");
+
+        self.get_all_pages(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::{Matcher, Server};
+
+    use super::*;
+
+    #[test]
+    fn test_get_all_organizations_on_github_com() -> Result<()> {
+        let client = crate::api::new(reqwest::blocking::Client::new(), "https://api.github.com")?;
+
+        assert!(client.get_all_organizations().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_all_organizations() -> Result<()> {
+        let mut server = Server::new();
+        let client = crate::api::new(reqwest::blocking::Client::new(), &server.url())?;
+
+        let first_page = server
+            .mock("GET", "/organizations")
+            .match_query(Matcher::Exact("per_page=100".into()))
+            .with_header(
+                "link",
+                &format!(
+                    r#"<{}/organizations?per_page=100&since=2>; rel="next""#,
+                    server.url()
+                ),
+            )
+            .with_body(r#"[{"login": "pajlada", "id": 1}, {"login": "pajlads", "id": 2}]"#)
+            .create();
+        let second_page = server
+            .mock("GET", "/organizations")
+            .match_query(Matcher::UrlEncoded("since".into(), "2".into()))
+            .with_body(r#"[{"login": "chatterino", "id": 3}]"#)
+            .create();
+
+        let organizations = client.get_all_organizations()?;
+
+        first_page.assert();
+        second_page.assert();
+        assert_eq!(
+            organizations
+                .iter()
+                .map(|organization| organization.login.as_str())
+                .collect::<Vec<_>>(),
+            ["pajlada", "pajlads", "chatterino"]
+        );
+
+        Ok(())
+    }
+}
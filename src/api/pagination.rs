@@ -1,4 +1,7 @@
 use reqwest::Url;
+use serde::de::DeserializeOwned;
+
+use crate::api::Client;
 
 pub(super) struct PaginationData {
     pub next: Option<Url>,
@@ -30,9 +33,8 @@ pub(super) fn get_pagination_data(
 
             let len = url_part.len();
 
-            let url = match Url::parse(&url_part[1..len - 1]) {
-                Ok(u) => u,
-                Err(_) => continue,
+            let Ok(url) = Url::parse(&url_part[1..len - 1]) else {
+                continue;
             };
 
             match rel_part {
@@ -46,10 +48,39 @@ pub(super) fn get_pagination_data(
                 "rel=\"prev\"" | "rel=\"first\"" | "rel=\"last\"" => {
                     // Valid values, but we don't care about them
                 }
-                e => return Err(anyhow::anyhow!("unknown rel: {}", e)),
+                e => return Err(anyhow::anyhow!("unknown rel: {e}")),
             }
         }
     }
 
     Ok(res)
 }
+
+impl Client {
+    /// Follows the pagination links starting at `url` and collects the items of every page
+    pub(super) fn get_all_pages<T: DeserializeOwned>(&self, url: Url) -> anyhow::Result<Vec<T>> {
+        self.get_all_wrapped_pages(url, |page: Vec<T>| page)
+    }
+
+    /// Like `get_all_pages`, for endpoints that wrap the items of a page in an object
+    pub(super) fn get_all_wrapped_pages<P: DeserializeOwned, T>(
+        &self,
+        url: Url,
+        items_of: impl Fn(P) -> Vec<T>,
+    ) -> anyhow::Result<Vec<T>> {
+        let mut items = Vec::new();
+
+        let mut pagination = PaginationData { next: Some(url) };
+
+        while let Some(url) = pagination.next {
+            let response = self.get(url)?.error_for_status()?;
+
+            pagination = get_pagination_data(response.headers())?;
+
+            let mut page = items_of(response.json()?);
+            items.append(&mut page);
+        }
+
+        Ok(items)
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde_json::json;
+use tracing::info;
+
+use crate::api::Client;
+use crate::models::{Collaborator, Team};
+
+impl Client {
+    pub fn get_repository_teams(&self, repo_owner: &str, repo_name: &str) -> Result<Vec<Team>> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/teams").as_str())?;
+
+        self.get_all_pages(url)
+    }
+
+    pub fn get_organization_team(
+        &self,
+        organization: &str,
+        team_slug: &str,
+    ) -> Result<Option<Team>> {
+        let url = self
+            .api_root
+            .join(format!("orgs/{organization}/teams/{team_slug}").as_str())?;
+
+        let response = self.get(url)?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        Ok(Some(response.error_for_status()?.json()?))
+    }
+
+    pub fn set_team_repository_permission(
+        &self,
+        organization: &str,
+        team_slug: &str,
+        repo_owner: &str,
+        repo_name: &str,
+        permission: &str,
+    ) -> Result<()> {
+        let url = self.api_root.join(
+            format!("orgs/{organization}/teams/{team_slug}/repos/{repo_owner}/{repo_name}")
+                .as_str(),
+        )?;
+
+        info!("Setting {team_slug} permission on {repo_owner}/{repo_name} to {permission}");
+
+        let body = json!({
+            "permission": permission,
+        });
+
+        self.client
+            .put(url)
+            .json(&body)
+            .send()?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    pub fn get_outside_collaborators(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+    ) -> Result<Vec<Collaborator>> {
+        let mut url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/collaborators").as_str())?;
+        url.query_pairs_mut().append_pair("affiliation", "outside");
+
+        self.get_all_pages(url)
+    }
+
+    pub fn remove_collaborator(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        login: &str,
+    ) -> Result<()> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/collaborators/{login}").as_str())?;
+
+        info!("Removing collaborator {login} from {repo_owner}/{repo_name}");
+
+        self.client.delete(url).send()?.error_for_status()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use serde_json::json;
+
+use crate::api::Client;
+use crate::models::PullRequest;
+
+impl Client {
+    pub fn find_open_pull_request(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        head_branch: &str,
+    ) -> Result<Option<PullRequest>> {
+        let mut url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/pulls").as_str())?;
+        url.query_pairs_mut()
+            .append_pair("state", "open")
+            .append_pair("head", format!("{repo_owner}:{head_branch}").as_str());
+
+        let response = self.get(url)?;
+
+        let pull_requests: Vec<PullRequest> = response.error_for_status()?.json()?;
+
+        Ok(pull_requests.into_iter().next())
+    }
+
+    pub fn create_pull_request(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        head_branch: &str,
+        base_branch: &str,
+        title: &str,
+        body: &str,
+    ) -> Result<PullRequest> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/pulls").as_str())?;
+
+        let body = json!({
+            "title": title,
+            "head": head_branch,
+            "base": base_branch,
+            "body": body,
+        });
+
+        let response = self.client.post(url).json(&body).send()?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+
+    pub fn update_pull_request(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        number: i64,
+        title: &str,
+        body: &str,
+    ) -> Result<PullRequest> {
+        let url = self
+            .api_root
+            .join(format!("repos/{repo_owner}/{repo_name}/pulls/{number}").as_str())?;
+
+        let body = json!({
+            "title": title,
+            "body": body,
+        });
+
+        let response = self.client.patch(url).json(&body).send()?;
+
+        Ok(response.error_for_status()?.json()?)
+    }
+}
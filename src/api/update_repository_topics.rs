@@ -26,7 +26,7 @@ impl Client {
 
         match response.status() {
             StatusCode::OK => Ok(()),
-            e => Err(anyhow::anyhow!("Error updating topics: {}", e)),
+            e => Err(anyhow::anyhow!("Error updating topics: {e}")),
         }
     }
 }
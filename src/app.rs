@@ -1,9 +1,22 @@
 use anyhow::Result;
+use std::collections::HashSet;
 use std::io::Write;
 
+use crate::config::FetchApi;
+use crate::consistency;
 use crate::context::Context;
-use crate::models::Repository;
-use crate::topic_operation::TopicOperation;
+use crate::custom_properties;
+use crate::environments::{DeploymentBranches, EnvironmentRule, Reviewer};
+use crate::file_sync::FileSyncResult;
+use crate::models::{
+    repository, BranchProtection, CustomPropertyAssignment, Environment, EnvironmentReviewerUpdate,
+    EnvironmentUpdate, LoadedBranchProtection, Repository,
+};
+use crate::permissions::OutsideCollaboratorAdmins;
+use crate::status_checks;
+use crate::targets::{RepositoryFilter, Targets};
+use crate::topic_operation::{self, RepositoryFacts};
+use crate::webhooks::WebhookChange;
 
 use console::{style, Style, Term};
 use tracing::{debug, error, info};
@@ -13,52 +26,324 @@ fn update_line<S: Into<String>>(terminal: &mut Term, msg: S) {
     terminal.write_all(msg.into().as_bytes()).unwrap();
 }
 
-pub fn run(
-    mut ctx: Context,
-    repos: Vec<&str>,
-    users: Vec<&str>,
-    organizations: Vec<&str>,
-) -> Result<()> {
+/// Asks the user to confirm a destructive fix.
+/// Without a terminal to ask on, the fix is only applied if --yes was passed.
+fn confirm(ctx: &mut Context, prompt: &str) -> Result<bool> {
+    if ctx.options.assume_yes {
+        return Ok(true);
+    }
+
+    if !ctx.terminal.is_term() {
+        println!("    Skipping, pass --yes to confirm: {prompt}");
+        return Ok(false);
+    }
+
+    ctx.terminal
+        .write_all(format!("    {prompt} [y/N] ").as_bytes())?;
+    let answer = ctx.terminal.read_line()?;
+
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Builds the full environment update, keeping the actual values for everything the rule
+/// doesn't care about
+fn environment_update(
+    ctx: &Context,
+    repo_owner: &str,
+    rule: &EnvironmentRule,
+    actual: Option<&Environment>,
+) -> Result<EnvironmentUpdate> {
+    let reviewers = match &rule.reviewers {
+        Some(reviewers) => reviewers
+            .iter()
+            .map(|reviewer| match reviewer {
+                Reviewer::User(login) => Ok(EnvironmentReviewerUpdate {
+                    type_: "User".to_string(),
+                    id: ctx.api_client.get_user_id(login)?,
+                }),
+                Reviewer::Team(slug) => Ok(EnvironmentReviewerUpdate {
+                    type_: "Team".to_string(),
+                    id: ctx
+                        .api_client
+                        .get_organization_team(repo_owner, slug)?
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("Team {slug} does not exist in {repo_owner}")
+                        })?
+                        .id,
+                }),
+            })
+            .collect::<Result<Vec<_>>>()?,
+        None => actual
+            .map(|actual| {
+                actual
+                    .reviewers()
+                    .iter()
+                    .map(|reviewer| EnvironmentReviewerUpdate {
+                        type_: reviewer.type_.clone(),
+                        id: reviewer.reviewer.id,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    Ok(EnvironmentUpdate {
+        wait_timer: rule
+            .wait_timer
+            .unwrap_or_else(|| actual.map_or(0, Environment::wait_timer)),
+        prevent_self_review: rule
+            .prevent_self_review
+            .unwrap_or_else(|| actual.is_some_and(Environment::prevent_self_review)),
+        reviewers,
+        deployment_branch_policy: rule.deployment_branches.as_ref().map_or_else(
+            || actual.and_then(|actual| actual.deployment_branch_policy),
+            DeploymentBranches::branch_policy,
+        ),
+    })
+}
+
+/// How many repositories of a set of repositories were checked
+#[derive(Debug, Default)]
+struct CheckSummary {
+    checked: usize,
+    skipped: usize,
+    failed: usize,
+}
+
+/// Loads the listed repositories that pass the filter and haven't been loaded yet
+fn load_repositories(
+    ctx: &mut Context,
+    source: &str,
+    listings: &[repository::Listing],
+    filter: &RepositoryFilter,
+    seen: &mut HashSet<String>,
+) -> Result<Vec<Repository>> {
+    let full_names: Vec<&str> = listings
+        .iter()
+        .filter(|listing| {
+            let selected = filter.matches(listing) && seen.insert(listing.full_name.clone());
+            if !selected {
+                debug!("Skipping repository {}", listing.full_name);
+            }
+            selected
+        })
+        .map(|listing| listing.full_name.as_str())
+        .collect();
+
+    fetch_repositories(ctx, source, &full_names)
+}
+
+/// Loads the repositories with the API selected in the config
+fn fetch_repositories(
+    ctx: &mut Context,
+    source: &str,
+    full_names: &[&str],
+) -> Result<Vec<Repository>> {
+    ctx.terminal
+        .write_all(format!("Loading repositories from {source}").as_bytes())?;
+
+    let repositories = match ctx.config.fetch_api {
+        FetchApi::Rest => {
+            let mut repositories = Vec::new();
+            for full_name in full_names {
+                ctx.terminal.clear_line()?;
+                ctx.terminal.write_all(
+                    format!("Loading repositories from {source} ({full_name})").as_bytes(),
+                )?;
+
+                repositories.push(ctx.api_client.get_repository(full_name)?);
+            }
+            repositories
+        }
+        FetchApi::Graphql => ctx.api_client.get_repositories_with_graphql(full_names)?,
+    };
+
+    ctx.terminal.write_line("")?;
+
+    Ok(repositories)
+}
+
+pub fn run(mut ctx: Context, targets: &Targets) -> Result<()> {
     let mut repositories: Vec<Repository> = Vec::new();
+    // Repositories can be listed by multiple sources, but should only be checked once
+    let mut seen = HashSet::new();
 
     info!("Expected repository settings: {:?}", ctx.config.settings);
 
-    for user in users {
-        repositories.append(
-            &mut ctx
-                .api_client
-                .get_repositories_from_user(&mut ctx.terminal, user)?,
-        );
+    // Fail before any repository is checked
+    if targets.all_organizations {
+        ctx.api_client.ensure_enterprise_server()?;
     }
 
-    for organization in organizations {
-        repositories.append(
-            &mut ctx
-                .api_client
-                .get_repositories_from_organization(&mut ctx.terminal, organization)?,
-        );
+    if let Some(filter) = &targets.me {
+        let listings = ctx
+            .api_client
+            .get_repositories_from_authenticated_user(filter)?;
+        repositories.append(&mut load_repositories(
+            &mut ctx,
+            "the authenticated user",
+            &listings,
+            &targets.filter,
+            &mut seen,
+        )?);
+    }
+
+    for user in &targets.users {
+        let listings = ctx.api_client.get_repositories_from_user(user)?;
+        repositories.append(&mut load_repositories(
+            &mut ctx,
+            &format!("user {user}"),
+            &listings,
+            &targets.filter,
+            &mut seen,
+        )?);
+    }
+
+    for organization in &targets.organizations {
+        let listings = ctx
+            .api_client
+            .get_repositories_from_organization(organization)?;
+        repositories.append(&mut load_repositories(
+            &mut ctx,
+            &format!("organization {organization}"),
+            &listings,
+            &targets.filter,
+            &mut seen,
+        )?);
+    }
+
+    for (organization, team_slug) in &targets.teams {
+        let listings = ctx
+            .api_client
+            .get_repositories_from_team(organization, team_slug)?;
+        repositories.append(&mut load_repositories(
+            &mut ctx,
+            &format!("team {organization}/{team_slug}"),
+            &listings,
+            &targets.filter,
+            &mut seen,
+        )?);
     }
 
-    for repo_owner_and_name in repos {
-        // TODO: Push into repositories
-        repositories.push(ctx.api_client.get_repository(repo_owner_and_name)?);
+    for query in &targets.searches {
+        let listings = ctx.api_client.get_repositories_from_search(query)?;
+        repositories.append(&mut load_repositories(
+            &mut ctx,
+            &format!("search '{query}'"),
+            &listings,
+            &targets.filter,
+            &mut seen,
+        )?);
+    }
+
+    if targets.installation {
+        let listings = ctx.api_client.get_repositories_from_installation()?;
+        repositories.append(&mut load_repositories(
+            &mut ctx,
+            "the app installation",
+            &listings,
+            &targets.filter,
+            &mut seen,
+        )?);
+    }
+
+    let full_names: Vec<&str> = targets
+        .repos
+        .iter()
+        .chain(&targets.listed_repos)
+        .filter(|full_name| seen.insert((*full_name).clone()))
+        .map(String::as_str)
+        .collect();
+    if !full_names.is_empty() {
+        repositories.append(&mut fetch_repositories(
+            &mut ctx,
+            "the command line",
+            &full_names,
+        )?);
     }
 
     check_repositories(&mut ctx, repositories)?;
 
+    if targets.all_organizations {
+        check_all_organizations(&mut ctx, &targets.filter, &mut seen)?;
+    }
+
     Ok(())
 }
 
-fn check_repositories(
+/// Checks the repositories of every organization of the GitHub instance, one organization at
+/// a time, and prints a summary for each organization
+fn check_all_organizations(
     ctx: &mut Context,
-    repositories: Vec<Repository>,
-) -> Result<(), anyhow::Error> {
-    for repository in repositories {
+    filter: &RepositoryFilter,
+    seen: &mut HashSet<String>,
+) -> Result<()> {
+    let organizations = ctx.api_client.get_all_organizations()?;
+    let mut failed_organizations = 0;
+
+    for organization in &organizations {
+        let login = &organization.login;
+
+        // A single organization we can't read shouldn't stop the others from being checked
+        let summary = ctx
+            .api_client
+            .get_repositories_from_organization(login)
+            .and_then(|listings| {
+                load_repositories(
+                    ctx,
+                    &format!("organization {login}"),
+                    &listings,
+                    filter,
+                    seen,
+                )
+            })
+            .and_then(|repositories| check_repositories(ctx, repositories));
+
+        match summary {
+            Ok(summary) => {
+                let line = format!(
+                    "Organization {login} - checked {} repositories, skipped {}, {} failed\n",
+                    summary.checked, summary.skipped, summary.failed
+                );
+                if summary.failed > 0 {
+                    ctx.terminal
+                        .write_all(style(line).red().to_string().as_bytes())?;
+                } else {
+                    ctx.terminal.write_all(line.as_bytes())?;
+                }
+            }
+            Err(e) => {
+                failed_organizations += 1;
+                ctx.terminal.write_line(
+                    style(format!(
+                        "Organization {login} - could not be checked: {e:#}"
+                    ))
+                    .red()
+                    .to_string()
+                    .as_str(),
+                )?;
+            }
+        }
+    }
+
+    ctx.terminal.write_line(&format!(
+        "Checked {} organizations, {} could not be checked",
+        organizations.len() - failed_organizations,
+        failed_organizations
+    ))?;
+
+    Ok(())
+}
+
+fn check_repositories(ctx: &mut Context, repositories: Vec<Repository>) -> Result<CheckSummary> {
+    let mut summary = CheckSummary::default();
+
+    for mut repository in repositories {
         // terminal.write_all("\n".as_bytes());
         let name_with_owner = repository.info.full_name.clone();
 
         // TODO: Move to the impl check_repository thing (or maybe filter out in list of repos?)
-        if repository.info.archived || repository.info.disabled {
+        if (repository.info.archived && !ctx.options.include_archived) || repository.info.disabled {
             let gray = Style::new().color256(242);
             ctx.terminal.clear_line()?;
             ctx.terminal.write_all(
@@ -72,150 +357,230 @@ fn check_repositories(
             //     "Skipping {} because it's archived or disabled",
             //     repository.name_with_owner
             // );
+            summary.skipped += 1;
             continue;
         }
+        // Archived repositories are read only, so their problems can only be reported
+        let (dry_run, dry_run_bpr) = (ctx.options.dry_run, ctx.options.dry_run_bpr);
+        ctx.options.dry_run |= repository.info.archived;
+        ctx.options.dry_run_bpr |= repository.info.archived;
         if let Err(e) = repository.check_repository(ctx) {
             error!("Error checking repository {}: {}", name_with_owner, e);
+            summary.failed += 1;
+        } else {
+            summary.checked += 1;
         }
+        (ctx.options.dry_run, ctx.options.dry_run_bpr) = (dry_run, dry_run_bpr);
     }
 
-    Ok(())
+    Ok(summary)
 }
 
 impl Repository {
-    #[allow(dead_code)]
+    /// Returns the branch protection of a branch, using the branch protection rules loaded
+    /// along with the repository if possible
+    fn branch_protection(
+        &self,
+        ctx: &Context,
+        branch_name: &str,
+    ) -> Result<Option<BranchProtection>> {
+        if self.branch_protection_rules.is_some() {
+            match self.loaded_branch_protection_rule(branch_name) {
+                // No rule applies, so the branch isn't protected
+                None => return Ok(None),
+                Some(LoadedBranchProtection {
+                    branch_protection: Some(branch_protection),
+                    ..
+                }) => return Ok(Some(branch_protection.clone())),
+                Some(_) => {}
+            }
+        }
+
+        ctx.api_client
+            .get_branch_protection(&self.info.owner.login, &self.info.name, branch_name)
+    }
+
     fn check_branch_protection_rules(&self, ctx: &mut Context) -> Result<()> {
-        if ctx.config.branch_protections.is_none() {
+        let Some(desired_branch_protections) = ctx.config.branch_protections.as_ref() else {
             return Ok(());
-        }
-        let desired_branch_protections = ctx.config.branch_protections.as_ref().unwrap();
+        };
         info!(
             "Check {} branch protections",
             desired_branch_protections.len()
         );
 
-        for desired_branch_protection in desired_branch_protections {
-            info!("Desired branch protection: {desired_branch_protection:?}");
-
-            let branch_name_pattern = desired_branch_protection.parsed_branch_name_pattern(self);
-
-            // TODO: Support special $branch_name_pattern
-            let branch_protection = ctx.api_client.get_branch_protection(
-                &self.info.owner.login,
-                &self.info.name,
-                branch_name_pattern,
-            )?;
-
-            info!("Actual branch protection: {branch_protection:#?}");
+        let repo_name = self.info.name.as_str();
+        let repo_owner = self.info.owner.login.as_str();
+        let repo_with_owner = self.info.full_name.as_str();
 
-            let diff = desired_branch_protection.diff(&branch_protection)?;
+        // Only loaded if a rule uses a glob pattern
+        let mut branches = None;
 
-            info!("Diff required: {diff:?}");
+        for desired_branch_protection in desired_branch_protections {
+            info!("Desired branch protection: {desired_branch_protection:?}");
 
-            if let Some(diff) = diff {
-                ctx.api_client.update_branch_protection(
-                    &self.info.owner.login,
-                    &self.info.name,
-                    branch_name_pattern,
-                    &diff,
-                )?;
+            if !custom_properties::matches(
+                desired_branch_protection.custom_properties(),
+                &self.custom_properties,
+            ) {
+                continue;
             }
-        }
 
-        Ok(())
-        /*
-        if self.branch_protection_rules.page_info.has_next_page {
-            return Err(anyhow::anyhow!(
-                "Repository {} has more than 100 branch protection rules. We cannot do anything",
-                repo_name
-            ));
-        }
+            let branch_names = if desired_branch_protection.is_glob() {
+                if branches.is_none() {
+                    branches = Some(ctx.api_client.get_branches(repo_owner, repo_name)?);
+                }
+                desired_branch_protection
+                    .matching_branches(branches.as_deref().unwrap_or_default())?
+            } else {
+                vec![desired_branch_protection
+                    .parsed_branch_name_pattern(self)
+                    .as_str()]
+            };
 
-        let mut actual_branch_protection_rules =
-            convert_branch_protection_rules_to_hashmap(self.branch_protection_rules.nodes.take())?;
+            for branch_name in branch_names {
+                let branch_protection = self.branch_protection(ctx, branch_name)?;
 
-        for (pattern, expected_branch_protection_rule) in &ctx.config.branch_protection_rules {
-            // info!(
-            //     "For pattern {} we expect {:?}",
-            //     pattern, expected_branch_protection_rule
-            // );
+                info!("Actual branch protection: {branch_protection:#?}");
 
-            if let Some(actual_branch_protection_rule) =
-                actual_branch_protection_rules.remove(pattern)
-            {
-                if let Some(diff) =
-                    expected_branch_protection_rule.diff(&actual_branch_protection_rule)
-                {
-                    let branch_protection_name = actual_branch_protection_rule.pattern.as_str();
-                    debug!("Repository {} Diff: {:?}", repo_with_owner, diff);
-                    let patch = diff.dump_patch(&actual_branch_protection_rule);
-                    debug!("Repository {} Patch: {:?}", repo_with_owner, patch);
-                    if !ctx.options.dry_run_bpr {
-                        let response = ctx.api_client.update_branch_protection(
+                if desired_branch_protection.should_delete(branch_protection.as_ref()) {
+                    if ctx.options.dry_run_bpr {
+                        println!(
+                            "Checking repository {repo_with_owner} branch protection {branch_name} - delete (DRY RUN)"
+                        );
+                    } else {
+                        println!(
+                            "Checking repository {repo_with_owner} branch protection {branch_name} - delete"
+                        );
+                        ctx.api_client.delete_branch_protection(
                             repo_owner,
                             repo_name,
-                            branch_protection_name,
-                            patch,
+                            branch_name,
                         )?;
-
-                        debug!("Response: {:?}", response);
-                        debug!("Response body: {:?}", response.text()?);
-                    } else {
-                        debug!(
-                            "DRY RUN: Update branch protection rule for {}",
-                            repo_with_owner
-                        );
                     }
-                    // Repo has a branch protection rule with this key, see if the values match!
+                    continue;
+                }
+
+                let diff = desired_branch_protection.diff(branch_protection.as_ref());
+
+                info!("Diff required: {diff:?}");
+
+                let Some(diff) = diff else {
+                    let gray = Style::new().color256(242);
+                    println!(
+                        "{}",
+                        gray.apply_to(format!(
+                            "Checking repository {repo_with_owner} branch protection {branch_name} - no changes needed"
+                        ))
+                    );
+                    continue;
+                };
+
+                if ctx.options.dry_run_bpr {
+                    println!(
+                        "Checking repository {repo_with_owner} branch protection {branch_name} - found {} differing settings (DRY RUN)",
+                        style(diff.differences.len()).cyan()
+                    );
                 } else {
-                    debug!(
-                        "Repository {} has a 100% matching branch protection rule:\n{:?}\n{:?}",
-                        repo_with_owner,
-                        actual_branch_protection_rule,
-                        expected_branch_protection_rule
+                    println!(
+                        "Checking repository {repo_with_owner} branch protection {branch_name} - found {} differing settings",
+                        style(diff.differences.len()).cyan()
                     );
                 }
-            } else {
-                // Repo does *not* have a branch protection rule with this key, should we create it?
-                // Maybe ask the user?
+                for difference in &diff.differences {
+                    println!("    {difference}");
+                }
+
+                if ctx.options.dry_run_bpr {
+                    continue;
+                }
+
+                if let Some(update) = &diff.update {
+                    ctx.api_client
+                        .update_branch_protection(repo_owner, repo_name, branch_name, update)?
+                        .error_for_status()?;
+                }
+                // The branch must be protected before signatures can be required
+                if let Some(required_signatures) = diff.required_signatures {
+                    ctx.api_client.set_required_signatures(
+                        repo_owner,
+                        repo_name,
+                        branch_name,
+                        required_signatures,
+                    )?;
+                }
             }
         }
-        */
+
+        Ok(())
     }
 
     fn check_topics(&self, ctx: &mut Context) -> Result<()> {
-        if ctx.config.topics.is_none() {
+        if ctx.config.topics.is_none() && ctx.config.topic_vocabulary.is_none() {
             return Ok(());
         }
-        let topics = ctx.config.topics.as_ref().unwrap();
+        let topics = ctx.config.topics.as_deref().unwrap_or_default();
 
-        let mut final_topics = self.topics.names.clone();
+        let mut facts = RepositoryFacts {
+            language: self.info.language.clone(),
+            fork: self.info.fork,
+            template: self.info.is_template,
+            existing_files: HashSet::new(),
+        };
+        for path in topic_operation::probed_files(topics) {
+            if ctx.api_client.file_exists(
+                self.info.owner.login.as_str(),
+                self.info.name.as_str(),
+                path,
+                self.info.default_branch.as_str(),
+            )? {
+                facts.existing_files.insert(path.to_string());
+            }
+        }
 
-        for operation in topics {
-            match operation {
-                TopicOperation::MustExist { name } => {
-                    final_topics.insert(name.clone());
-                }
-                TopicOperation::MustNotExist { name } => {
-                    final_topics.remove(name);
-                }
-                TopicOperation::Rename { old_name, name } => {
-                    if final_topics.remove(old_name) {
-                        final_topics.insert(name.clone());
-                    }
-                }
+        let mut final_topics = topic_operation::apply(topics, &self.topics.names, &facts);
+
+        let mut reported_unknown_topics = false;
+        if let Some(vocabulary) = ctx.config.topic_vocabulary.as_ref() {
+            let unknown = vocabulary.unknown(&final_topics);
+            if !unknown.is_empty() && !vocabulary.remove_unknown {
+                reported_unknown_topics = true;
+                println!(
+                    "Checking repository {} topics - found {} topics outside the vocabulary: {:?}",
+                    self.info.full_name,
+                    style(unknown.len()).cyan(),
+                    unknown,
+                );
+            }
+            if vocabulary.remove_unknown {
+                final_topics.retain(|topic| vocabulary.allowed.contains(topic));
+            }
+        }
+
+        if self.topics.names != final_topics {
+            // Checked up front so GitHub doesn't refuse the update
+            if let Err(e) = topic_operation::validate_topics(&final_topics) {
+                println!(
+                    "Checking repository {} topics - {}",
+                    self.info.full_name,
+                    style(format!("can't update topics: {e}")).red()
+                );
+                return Ok(());
             }
         }
 
         if self.topics.names == final_topics {
-            let gray = Style::new().color256(242);
-            println!(
-                "{}",
-                gray.apply_to(format!(
-                    "Checking repository {} topics - no changes needed",
-                    self.info.full_name
-                ))
-            );
+            // Saying nothing needs to change would contradict the unknown topics reported above
+            if !reported_unknown_topics {
+                let gray = Style::new().color256(242);
+                println!(
+                    "{}",
+                    gray.apply_to(format!(
+                        "Checking repository {} topics - no changes needed",
+                        self.info.full_name
+                    ))
+                );
+            }
         } else if ctx.options.dry_run {
             println!(
                 "Checking repository {} topics - add({:?}), del({:?}) (DRY RUN)",
@@ -309,13 +674,744 @@ impl Repository {
         Ok(())
     }
 
-    fn check_repository(&self, ctx: &mut Context) -> Result<()> {
-        // self.check_branch_protection_rules(ctx)?;
+    fn check_custom_properties(&self, ctx: &mut Context) -> Result<()> {
+        let Some(desired_custom_properties) = ctx.config.custom_properties.as_ref() else {
+            return Ok(());
+        };
+        if self.info.owner.owner_type != "Organization" {
+            return Ok(());
+        }
+
+        let repo_name = self.info.name.as_str();
+        let repo_owner = self.info.owner.login.as_str();
+        let repo_with_owner = self.info.full_name.as_str();
+
+        if !ctx.custom_property_schemas.contains_key(repo_owner) {
+            let schema = ctx
+                .api_client
+                .get_organization_custom_properties(repo_owner)?;
+            ctx.custom_property_schemas
+                .insert(repo_owner.to_string(), schema);
+        }
+        let schema = &ctx.custom_property_schemas[repo_owner];
+
+        let violations = desired_custom_properties.violations(schema, &self.custom_properties);
+
+        if violations.is_empty() {
+            let gray = Style::new().color256(242);
+            println!(
+                "{}",
+                gray.apply_to(format!(
+                    "Checking repository {repo_with_owner} custom properties - no changes needed"
+                ))
+            );
+            return Ok(());
+        }
+
+        if ctx.options.dry_run {
+            println!(
+                "Checking repository {} custom properties - found {} differing custom properties (DRY RUN)",
+                repo_with_owner,
+                style(violations.len()).cyan()
+            );
+        } else {
+            println!(
+                "Checking repository {} custom properties - found {} differing custom properties",
+                repo_with_owner,
+                style(violations.len()).cyan()
+            );
+        }
+
+        let mut updates = Vec::new();
+        for violation in &violations {
+            match &violation.fix {
+                Some(fix) => {
+                    println!(
+                        "    {}: {:?} -> {:?}",
+                        violation.name,
+                        violation.actual,
+                        fix.values()
+                    );
+                    updates.push(CustomPropertyAssignment {
+                        property_name: violation.name.clone(),
+                        value: Some(fix.clone()),
+                    });
+                }
+                None => println!(
+                    "    {}: {:?} is not allowed, and there is no default value to set",
+                    violation.name, violation.actual
+                ),
+            }
+        }
+
+        if !ctx.options.dry_run && !updates.is_empty() {
+            ctx.api_client
+                .update_repository_custom_properties(repo_owner, repo_name, &updates)?;
+        }
+
+        Ok(())
+    }
+
+    fn check_default_branch(&mut self, ctx: &mut Context) -> Result<()> {
+        let Some(default_branch) = ctx.config.default_branch.as_ref() else {
+            return Ok(());
+        };
+
+        let repo_with_owner = self.info.full_name.as_str();
+        let current_name = self.info.default_branch.as_str();
+
+        let Some(desired_name) = default_branch.diff(&self.info) else {
+            let gray = Style::new().color256(242);
+            println!(
+                "{}",
+                gray.apply_to(format!(
+                    "Checking repository {repo_with_owner} default branch - no changes needed"
+                ))
+            );
+            return Ok(());
+        };
+
+        if ctx.options.dry_run {
+            println!(
+                "Checking repository {repo_with_owner} default branch - rename {current_name} to {desired_name} (DRY RUN)"
+            );
+            return Ok(());
+        }
+
+        println!(
+            "Checking repository {repo_with_owner} default branch - rename {current_name} to {desired_name}"
+        );
+        ctx.api_client.rename_branch(
+            &self.info.owner.login,
+            &self.info.name,
+            current_name,
+            desired_name,
+        )?;
+
+        // The remaining checks should act on the renamed branch
+        self.info.default_branch = desired_name.to_string();
+
+        Ok(())
+    }
+
+    fn check_file_sync(&self, ctx: &mut Context) -> Result<()> {
+        let Some(file_sync) = ctx.config.file_sync.as_ref() else {
+            return Ok(());
+        };
+
+        let repo_name = self.info.name.as_str();
+        let repo_owner = self.info.owner.login.as_str();
+        let repo_with_owner = self.info.full_name.as_str();
+        let default_branch = self.info.default_branch.as_str();
+
+        let changes =
+            ctx.api_client
+                .diff_files(repo_owner, repo_name, default_branch, &file_sync.files)?;
+
+        if changes.is_empty() {
+            let gray = Style::new().color256(242);
+            println!(
+                "{}",
+                gray.apply_to(format!(
+                    "Checking repository {repo_with_owner} files - no changes needed"
+                ))
+            );
+            return Ok(());
+        }
+
+        if ctx.options.dry_run {
+            println!(
+                "Checking repository {} files - found {} differing files (DRY RUN)",
+                repo_with_owner,
+                style(changes.len()).cyan()
+            );
+        } else {
+            println!(
+                "Checking repository {} files - found {} differing files",
+                repo_with_owner,
+                style(changes.len()).cyan()
+            );
+        }
+
+        for change in &changes {
+            if change.exists {
+                println!("    Update {}", change.path);
+            } else {
+                println!("    Create {}", change.path);
+            }
+        }
+
+        if ctx.options.dry_run {
+            return Ok(());
+        }
+
+        match ctx.api_client.sync_files(
+            repo_owner,
+            repo_name,
+            default_branch,
+            file_sync,
+            &changes,
+        )? {
+            FileSyncResult::Opened(pr) => {
+                println!("    Opened pull request #{} ({})", pr.number, pr.html_url);
+            }
+            FileSyncResult::Updated(pr) => {
+                println!("    Updated pull request #{} ({})", pr.number, pr.html_url);
+            }
+            FileSyncResult::UpToDate(pr) => {
+                println!(
+                    "    Pull request #{} is already up to date ({})",
+                    pr.number, pr.html_url
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_permissions(&self, ctx: &mut Context) -> Result<()> {
+        let Some(permissions) = ctx.config.permissions.as_ref() else {
+            return Ok(());
+        };
+
+        let repo_name = self.info.name.as_str();
+        let repo_owner = self.info.owner.login.as_str();
+        let repo_with_owner = self.info.full_name.as_str();
+
+        // Teams only exist in organizations
+        let team_changes = if self.info.owner.owner_type == "Organization" {
+            let actual_teams = ctx.api_client.get_repository_teams(repo_owner, repo_name)?;
+            permissions.diff_teams(self, &actual_teams)
+        } else {
+            Vec::new()
+        };
+
+        let outside_admins =
+            if permissions.outside_collaborator_admins == OutsideCollaboratorAdmins::Allow {
+                Vec::new()
+            } else {
+                ctx.api_client
+                    .get_outside_collaborators(repo_owner, repo_name)?
+                    .into_iter()
+                    .filter(crate::models::Collaborator::is_admin)
+                    .collect()
+            };
+
+        let change_count = team_changes.len() + outside_admins.len();
+
+        if change_count == 0 {
+            let gray = Style::new().color256(242);
+            println!(
+                "{}",
+                gray.apply_to(format!(
+                    "Checking repository {repo_with_owner} permissions - no changes needed"
+                ))
+            );
+            return Ok(());
+        }
+
+        if ctx.options.dry_run {
+            println!(
+                "Checking repository {} permissions - found {} differing permissions (DRY RUN)",
+                repo_with_owner,
+                style(change_count).cyan()
+            );
+        } else {
+            println!(
+                "Checking repository {} permissions - found {} differing permissions",
+                repo_with_owner,
+                style(change_count).cyan()
+            );
+        }
+
+        for change in &team_changes {
+            println!(
+                "    Set team {} permission to {} (currently {})",
+                change.team,
+                change.desired,
+                change.actual.as_deref().unwrap_or("no access")
+            );
+
+            if ctx.options.dry_run {
+                continue;
+            }
+
+            if ctx
+                .api_client
+                .get_organization_team(repo_owner, change.team)?
+                .is_none()
+            {
+                error!("Team {} does not exist in {}", change.team, repo_owner);
+                continue;
+            }
+
+            ctx.api_client.set_team_repository_permission(
+                repo_owner,
+                change.team,
+                repo_owner,
+                repo_name,
+                change.desired.as_str(),
+            )?;
+        }
+
+        for collaborator in &outside_admins {
+            if permissions.outside_collaborator_admins == OutsideCollaboratorAdmins::Remove {
+                println!(
+                    "    Remove outside collaborator {} with admin access",
+                    collaborator.login
+                );
+
+                if !ctx.options.dry_run {
+                    ctx.api_client.remove_collaborator(
+                        repo_owner,
+                        repo_name,
+                        &collaborator.login,
+                    )?;
+                }
+            } else {
+                println!(
+                    "    Outside collaborator {} has admin access",
+                    collaborator.login
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_webhooks(&self, ctx: &mut Context) -> Result<()> {
+        let Some(webhooks) = ctx.config.webhooks.as_ref() else {
+            return Ok(());
+        };
+
+        let repo_name = self.info.name.as_str();
+        let repo_owner = self.info.owner.login.as_str();
+        let repo_with_owner = self.info.full_name.as_str();
+
+        let actual_hooks = ctx.api_client.get_repository_hooks(repo_owner, repo_name)?;
+
+        let changes = webhooks.diff(&actual_hooks);
+        // Deleted hooks no longer have deliveries to check
+        let mut deleted_hooks = HashSet::new();
+
+        if changes.is_empty() {
+            let gray = Style::new().color256(242);
+            println!(
+                "{}",
+                gray.apply_to(format!(
+                    "Checking repository {repo_with_owner} webhooks - no changes needed"
+                ))
+            );
+        } else {
+            if ctx.options.dry_run {
+                println!(
+                    "Checking repository {} webhooks - found {} differing webhooks (DRY RUN)",
+                    repo_with_owner,
+                    style(changes.len()).cyan()
+                );
+            } else {
+                println!(
+                    "Checking repository {} webhooks - found {} differing webhooks",
+                    repo_with_owner,
+                    style(changes.len()).cyan()
+                );
+            }
+
+            for change in &changes {
+                match change {
+                    WebhookChange::Create(hook) => {
+                        println!("    Create {}", hook.url);
+                        if !ctx.options.dry_run {
+                            ctx.api_client
+                                .create_repository_hook(repo_owner, repo_name, hook)?;
+                        }
+                    }
+                    WebhookChange::Update {
+                        id,
+                        hook,
+                        differences,
+                    } => {
+                        println!("    Update {} ({})", hook.url, differences.join(", "));
+                        if !ctx.options.dry_run {
+                            ctx.api_client
+                                .update_repository_hook(repo_owner, repo_name, *id, hook)?;
+                        }
+                    }
+                    WebhookChange::Delete { id, url } => {
+                        println!("    Delete {url}");
+                        if !ctx.options.dry_run {
+                            ctx.api_client
+                                .delete_repository_hook(repo_owner, repo_name, *id)?;
+                            deleted_hooks.insert(*id);
+                        }
+                    }
+                }
+            }
+        }
+
+        if webhooks.check_deliveries {
+            for hook in actual_hooks
+                .iter()
+                .filter(|hook| hook.active && !deleted_hooks.contains(&hook.id))
+            {
+                let deliveries = ctx.api_client.get_recent_hook_deliveries(
+                    repo_owner,
+                    repo_name,
+                    hook.id,
+                    webhooks.recent_deliveries,
+                )?;
+                let failed = deliveries.iter().filter(|d| d.failed()).count();
+                if failed > 0 {
+                    println!(
+                        "Checking repository {} webhooks - {} has {} failing deliveries out of the last {}",
+                        repo_with_owner,
+                        hook.config.url.as_deref().unwrap_or(&hook.name),
+                        style(failed).red(),
+                        deliveries.len()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_status_checks(
+        &self,
+        ctx: &mut Context,
+        default_branch_protection: Option<&BranchProtection>,
+    ) -> Result<()> {
+        let Some(status_checks) = ctx.config.status_checks.as_ref() else {
+            return Ok(());
+        };
+
+        let repo_name = self.info.name.as_str();
+        let repo_owner = self.info.owner.login.as_str();
+        let repo_with_owner = self.info.full_name.as_str();
+        let default_branch = self.info.default_branch.as_str();
+
+        let mut required_checks = default_branch_protection
+            .and_then(|branch_protection| branch_protection.required_status_checks.as_ref())
+            .map(status_checks::required_checks)
+            .unwrap_or_default();
+        for rule in ctx.config.branch_protections.iter().flatten() {
+            if !custom_properties::matches(rule.custom_properties(), &self.custom_properties)
+                || !rule.protects(self, default_branch)?
+            {
+                continue;
+            }
+            for check in rule.required_status_checks() {
+                if !required_checks.iter().any(|required_check| {
+                    required_check.context == check.context && required_check.app_id == check.app_id
+                }) {
+                    required_checks.push(check);
+                }
+            }
+        }
+
+        if required_checks.is_empty() {
+            return Ok(());
+        }
+
+        let mut statuses = Vec::new();
+        let mut check_runs = Vec::new();
+        for commit in ctx.api_client.get_recent_commits(
+            repo_owner,
+            repo_name,
+            default_branch,
+            status_checks.recent_commits,
+        )? {
+            statuses.extend(ctx.api_client.get_commit_statuses(
+                repo_owner,
+                repo_name,
+                &commit.sha,
+            )?);
+            check_runs.extend(
+                ctx.api_client
+                    .get_check_runs(repo_owner, repo_name, &commit.sha)?,
+            );
+        }
+
+        let problems = status_checks::validate(&required_checks, &statuses, &check_runs);
+
+        if problems.is_empty() {
+            let gray = Style::new().color256(242);
+            println!(
+                "{}",
+                gray.apply_to(format!(
+                    "Checking repository {repo_with_owner} status checks - no problems found"
+                ))
+            );
+            return Ok(());
+        }
+
+        println!(
+            "Checking repository {} status checks - found {} problems with the required status checks of {} in the last {} commits",
+            repo_with_owner,
+            style(problems.len()).cyan(),
+            default_branch,
+            status_checks.recent_commits,
+        );
+        for problem in &problems {
+            println!("    {problem}");
+        }
+
+        Ok(())
+    }
+
+    fn check_consistency(
+        &self,
+        ctx: &mut Context,
+        default_branch_protection: Option<&BranchProtection>,
+    ) -> Result<()> {
+        if !ctx.config.consistency_checks {
+            return Ok(());
+        }
+
+        let repo_name = self.info.name.as_str();
+        let repo_owner = self.info.owner.login.as_str();
+        let repo_with_owner = self.info.full_name.as_str();
+        let default_branch = self.info.default_branch.as_str();
+
+        let mut has_codeowners = false;
+        if consistency::requires_code_owner_reviews(default_branch_protection) {
+            for path in consistency::CODEOWNERS_PATHS {
+                if ctx
+                    .api_client
+                    .file_exists(repo_owner, repo_name, path, default_branch)?
+                {
+                    has_codeowners = true;
+                    break;
+                }
+            }
+        }
+
+        let inconsistencies =
+            consistency::check(&self.info, default_branch_protection, has_codeowners);
+
+        if inconsistencies.is_empty() {
+            let gray = Style::new().color256(242);
+            println!(
+                "{}",
+                gray.apply_to(format!(
+                    "Checking repository {repo_with_owner} consistency - no problems found"
+                ))
+            );
+            return Ok(());
+        }
+
+        println!(
+            "Checking repository {} consistency - found {} inconsistencies",
+            repo_with_owner,
+            style(inconsistencies.len()).cyan()
+        );
+        for inconsistency in &inconsistencies {
+            println!("    {inconsistency}");
+        }
+
+        Ok(())
+    }
+
+    fn check_deploy_keys(&self, ctx: &mut Context) -> Result<()> {
+        let Some(deploy_keys) = ctx.config.deploy_keys.as_ref() else {
+            return Ok(());
+        };
+
+        let repo_name = self.info.name.as_str();
+        let repo_owner = self.info.owner.login.as_str();
+        let repo_with_owner = self.info.full_name.as_str();
+
+        let now = chrono::Utc::now();
+        let disallowed_keys: Vec<_> = ctx
+            .api_client
+            .get_deploy_keys(repo_owner, repo_name)?
+            .into_iter()
+            .filter_map(|key| {
+                let violations = deploy_keys.violations(&key, now);
+                (!violations.is_empty()).then_some((key, violations))
+            })
+            .collect();
+
+        if disallowed_keys.is_empty() {
+            let gray = Style::new().color256(242);
+            println!(
+                "{}",
+                gray.apply_to(format!(
+                    "Checking repository {repo_with_owner} deploy keys - no changes needed"
+                ))
+            );
+            return Ok(());
+        }
+
+        if ctx.options.dry_run {
+            println!(
+                "Checking repository {} deploy keys - found {} disallowed keys (DRY RUN)",
+                repo_with_owner,
+                style(disallowed_keys.len()).cyan()
+            );
+        } else {
+            println!(
+                "Checking repository {} deploy keys - found {} disallowed keys",
+                repo_with_owner,
+                style(disallowed_keys.len()).cyan()
+            );
+        }
+
+        for (key, violations) in &disallowed_keys {
+            let reasons: Vec<String> = violations.iter().map(ToString::to_string).collect();
+            println!("    Deploy key {} {}", key.title, reasons.join(", "));
+
+            if ctx.options.dry_run {
+                continue;
+            }
+
+            if confirm(
+                ctx,
+                format!("Delete deploy key {} from {repo_with_owner}?", key.title).as_str(),
+            )? {
+                ctx.api_client
+                    .delete_deploy_key(repo_owner, repo_name, key.id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_environments(&self, ctx: &mut Context) -> Result<()> {
+        let Some(environments) = ctx.config.environments.as_ref() else {
+            return Ok(());
+        };
+
+        let repo_name = self.info.name.as_str();
+        let repo_owner = self.info.owner.login.as_str();
+        let repo_with_owner = self.info.full_name.as_str();
+
+        for rule in environments {
+            if !custom_properties::matches(rule.custom_properties.as_ref(), &self.custom_properties)
+            {
+                continue;
+            }
+
+            let environment = rule.name.as_str();
+            let actual = ctx
+                .api_client
+                .get_environment(repo_owner, repo_name, environment)?;
+
+            let actual_branch_policies = match actual
+                .as_ref()
+                .and_then(|actual| actual.deployment_branch_policy)
+            {
+                Some(policy) if policy.custom_branch_policies => ctx
+                    .api_client
+                    .get_deployment_branch_policies(repo_owner, repo_name, environment)?,
+                _ => Vec::new(),
+            };
+
+            let diff = rule.diff(self, actual.as_ref(), &actual_branch_policies);
+
+            if diff.is_empty() {
+                let gray = Style::new().color256(242);
+                println!(
+                    "{}",
+                    gray.apply_to(format!(
+                        "Checking repository {repo_with_owner} environment {environment} - no changes needed"
+                    ))
+                );
+                continue;
+            }
+
+            if ctx.options.dry_run {
+                println!(
+                    "Checking repository {repo_with_owner} environment {environment} - found differences (DRY RUN)"
+                );
+            } else {
+                println!(
+                    "Checking repository {repo_with_owner} environment {environment} - found differences"
+                );
+            }
+
+            for difference in &diff.differences {
+                println!("    {difference}");
+            }
+            for pattern in &diff.branch_policies_to_add {
+                println!("    Allow deployments from {pattern}");
+            }
+            for (_, pattern) in &diff.branch_policies_to_remove {
+                println!("    Disallow deployments from {pattern}");
+            }
+
+            if ctx.options.dry_run {
+                continue;
+            }
+
+            if !diff.differences.is_empty() {
+                let update = environment_update(ctx, repo_owner, rule, actual.as_ref())?;
+                ctx.api_client
+                    .update_environment(repo_owner, repo_name, environment, &update)?;
+            }
+
+            for pattern in &diff.branch_policies_to_add {
+                ctx.api_client.create_deployment_branch_policy(
+                    repo_owner,
+                    repo_name,
+                    environment,
+                    pattern,
+                )?;
+            }
+            for (policy_id, _) in &diff.branch_policies_to_remove {
+                ctx.api_client.delete_deployment_branch_policy(
+                    repo_owner,
+                    repo_name,
+                    environment,
+                    *policy_id,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_repository(&mut self, ctx: &mut Context) -> Result<()> {
+        // Custom properties only exist for repositories owned by an organization
+        if ctx.config.uses_custom_properties() && self.info.owner.owner_type == "Organization" {
+            self.custom_properties = custom_properties::by_name(
+                ctx.api_client
+                    .get_repository_custom_properties(&self.info.owner.login, &self.info.name)?,
+            );
+        }
+
+        self.check_default_branch(ctx)?;
+
+        self.check_branch_protection_rules(ctx)?;
+
+        // Only read by the status check and consistency checks, so it's loaded once for both
+        let default_branch_protection =
+            if ctx.config.status_checks.is_some() || ctx.config.consistency_checks {
+                self.branch_protection(ctx, &self.info.default_branch)?
+            } else {
+                None
+            };
+
+        self.check_status_checks(ctx, default_branch_protection.as_ref())?;
 
         self.check_topics(ctx)?;
 
         self.check_settings(ctx)?;
 
+        self.check_custom_properties(ctx)?;
+
+        self.check_permissions(ctx)?;
+
+        self.check_webhooks(ctx)?;
+
+        self.check_deploy_keys(ctx)?;
+
+        self.check_environments(ctx)?;
+
+        self.check_file_sync(ctx)?;
+
+        self.check_consistency(ctx, default_branch_protection.as_ref())?;
+
         Ok(())
     }
 }
@@ -333,3 +1429,116 @@ impl Repository {
 //
 //     Ok(rule_map)
 // }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use mockito::{Matcher, Server};
+
+    use super::*;
+
+    fn context(server: &Server, config: &str, fix: bool) -> Result<Context> {
+        Ok(Context {
+            config: crate::config::load_from_reader(std::io::Cursor::new(config))?,
+            terminal: Term::stdout(),
+            api_client: crate::api::new(reqwest::blocking::Client::new(), &server.url())?,
+            options: crate::options::Options {
+                dry_run: !fix,
+                dry_run_bpr: !fix,
+                assume_yes: true,
+                include_archived: false,
+            },
+            custom_property_schemas: HashMap::new(),
+        })
+    }
+
+    fn repository(name: &str) -> Repository {
+        Repository::for_test(repository::Info::for_test(
+            &format!("pajlada/{name}"),
+            serde_json::json!({}),
+        ))
+    }
+
+    #[test]
+    fn test_check_webhooks_skips_deliveries_of_deleted_hooks() -> Result<()> {
+        let mut server = Server::new();
+        let mut ctx = context(
+            &server,
+            r#"{"webhooks": {
+                "hooks": [{"url": "https://ci.example.com/hook", "events": ["push"]}],
+                "forbid_unknown": true,
+                "check_deliveries": true
+            }}"#,
+            true,
+        )?;
+
+        let hooks = server
+            .mock("GET", "/repos/pajlada/pajbot/hooks")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"[
+                    {"id": 1, "name": "web", "active": true, "events": ["push"],
+                     "config": {"url": "https://ci.example.com/hook", "content_type": "json", "insecure_ssl": "0"}},
+                    {"id": 2, "name": "web", "active": true, "events": ["push"],
+                     "config": {"url": "https://unknown.example.com", "content_type": "json", "insecure_ssl": "0"}}
+                ]"#,
+            )
+            .create();
+        let delete = server
+            .mock("DELETE", "/repos/pajlada/pajbot/hooks/2")
+            .with_status(204)
+            .create();
+        let deliveries = server
+            .mock("GET", "/repos/pajlada/pajbot/hooks/1/deliveries")
+            .match_query(Matcher::Any)
+            .with_body(r#"[{"status_code": 200}]"#)
+            .create();
+        let deleted_deliveries = server
+            .mock("GET", "/repos/pajlada/pajbot/hooks/2/deliveries")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .expect(0)
+            .create();
+
+        repository("pajbot").check_webhooks(&mut ctx)?;
+
+        hooks.assert();
+        delete.assert();
+        deliveries.assert();
+        deleted_deliveries.assert();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_repository_reuses_loaded_branch_protection() -> Result<()> {
+        let mut server = Server::new();
+        let mut ctx = context(
+            &server,
+            r#"{"status_checks": {}, "consistency_checks": true}"#,
+            false,
+        )?;
+
+        let protection = server
+            .mock("GET", "/repos/pajlada/pajbot/branches/master/protection")
+            .expect(0)
+            .create();
+        let commits = server
+            .mock("GET", "/repos/pajlada/pajbot/commits")
+            .match_query(Matcher::Any)
+            .with_body("[]")
+            .expect(0)
+            .create();
+
+        // Loaded through GraphQL, without a rule for the default branch
+        let mut repository = repository("pajbot");
+        repository.branch_protection_rules = Some(Vec::new());
+        repository.check_repository(&mut ctx)?;
+
+        protection.assert();
+        commits.assert();
+
+        Ok(())
+    }
+}
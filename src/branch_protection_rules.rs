@@ -1,7 +1,12 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::Deserialize;
 
-use crate::models::{BranchProtection, BranchProtectionUpdate};
+use crate::constraint::Constraint;
+use crate::custom_properties::CustomPropertySelector;
+use crate::models::{
+    Branch, BranchProtection, BranchProtectionUpdate, ListOfUserTeamsOrApps,
+    ProtectedBranchRequiredStatusCheckChecksItem,
+};
 
 #[allow(unused_macros)]
 macro_rules! ensure_same {
@@ -21,9 +26,40 @@ macro_rules! ensure_same {
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
 pub enum BranchProtectionOperation {
     MustExist,
     MayExist,
+    MustNotExist,
+}
+
+/// Who can push to a protected branch. Teams and apps are referenced by their slugs
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct PushRestrictions {
+    #[serde(default)]
+    users: Vec<String>,
+    #[serde(default)]
+    teams: Vec<String>,
+    #[serde(default)]
+    apps: Vec<String>,
+}
+
+impl From<&PushRestrictions> for ListOfUserTeamsOrApps {
+    fn from(restrictions: &PushRestrictions) -> Self {
+        ListOfUserTeamsOrApps::new(
+            restrictions.users.clone(),
+            restrictions.teams.clone(),
+            restrictions.apps.clone(),
+        )
+    }
+}
+
+/// A status check that must report on the protected branch
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct RequiredStatusCheck {
+    context: String,
+    /// The app that must report the status check. None means any app can report it
+    app_id: Option<i64>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -32,9 +68,85 @@ pub struct BranchProtectionRule {
     operation: BranchProtectionOperation,
     is_admin_enforced: Option<bool>,
     allow_deletions: Option<bool>,
+    restrictions: Option<PushRestrictions>,
+    required_approving_review_count: Option<Constraint<i64>>,
+    /// Enforced through its own endpoint, since the branch protection update ignores it
+    required_signatures: Option<bool>,
+    /// Validated by the status checks check. The required status checks of the branch
+    /// protection itself are not changed
+    required_status_checks: Option<Vec<RequiredStatusCheck>>,
+    /// Only apply this rule to repositories with the given custom property values
+    custom_properties: Option<CustomPropertySelector>,
 }
 
 impl BranchProtectionRule {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(constraint) = &self.required_approving_review_count {
+            constraint.validate().with_context(|| {
+                format!(
+                    "Invalid required_approving_review_count for {}",
+                    self.branch_name_pattern
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+
+    pub fn custom_properties(&self) -> Option<&CustomPropertySelector> {
+        self.custom_properties.as_ref()
+    }
+
+    /// Whether the branch name pattern is a glob pattern (e.g. `release/*`) that can match
+    /// multiple branches
+    pub fn is_glob(&self) -> bool {
+        self.branch_name_pattern.contains(['*', '?', '['])
+    }
+
+    /// Returns the names of the branches matching the glob branch name pattern.
+    /// Like on GitHub, `*` does not match `/`
+    pub fn matching_branches<'a>(&self, branches: &'a [Branch]) -> Result<Vec<&'a str>> {
+        let pattern = glob::Pattern::new(&self.branch_name_pattern)?;
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..glob::MatchOptions::new()
+        };
+
+        Ok(branches
+            .iter()
+            .map(|branch| branch.name.as_str())
+            .filter(|name| pattern.matches_with(name, options))
+            .collect())
+    }
+
+    /// Whether the rule wants the given branch of the repository to be protected
+    pub fn protects(&self, repo: &crate::models::Repository, branch_name: &str) -> Result<bool> {
+        if self.operation == BranchProtectionOperation::MustNotExist {
+            return Ok(false);
+        }
+
+        if self.is_glob() {
+            let branch = Branch {
+                name: branch_name.to_string(),
+            };
+            Ok(!self.matching_branches(&[branch])?.is_empty())
+        } else {
+            Ok(self.parsed_branch_name_pattern(repo) == branch_name)
+        }
+    }
+
+    /// The status checks the rule requires on its branches
+    pub fn required_status_checks(&self) -> Vec<ProtectedBranchRequiredStatusCheckChecksItem> {
+        self.required_status_checks
+            .iter()
+            .flatten()
+            .map(|check| ProtectedBranchRequiredStatusCheckChecksItem {
+                app_id: check.app_id,
+                context: check.context.clone(),
+            })
+            .collect()
+    }
+
     pub fn parsed_branch_name_pattern<'a>(
         &'a self,
         repo: &'a crate::models::Repository,
@@ -46,37 +158,136 @@ impl BranchProtectionRule {
         }
     }
 
+    /// Whether the actual branch protection must be deleted
+    pub fn should_delete(&self, actual_branch_protection: Option<&BranchProtection>) -> bool {
+        self.operation == BranchProtectionOperation::MustNotExist
+            && actual_branch_protection.is_some()
+    }
+
+    /// Returns the desired required signatures value if it differs from the actual one
+    fn required_signatures_diff(
+        &self,
+        actual_branch_protection: Option<&BranchProtection>,
+    ) -> Option<bool> {
+        let desired = self.required_signatures?;
+        let actual = actual_branch_protection
+            .and_then(|actual_branch_protection| actual_branch_protection.required_signatures)
+            .unwrap_or(false);
+
+        (desired != actual).then_some(desired)
+    }
+
+    /// Compares the rule against the actual branch protection.
+    /// Returns None if the branch protection already complies with the rule
     pub fn diff(
         &self,
-        actual_branch_protection: &Option<BranchProtection>,
-    ) -> Result<Option<BranchProtectionUpdate>> {
-        let mut diff = if let Some(actual_branch_protection) = actual_branch_protection {
-            BranchProtectionUpdate::from(actual_branch_protection)
-        } else {
-            match self.operation {
-                BranchProtectionOperation::MayExist => {
-                    // The repo does not contain a branch protection rule with this pattern
-                    // We do not require a branch to be created
-                    return Ok(None);
-                }
-                BranchProtectionOperation::MustExist => BranchProtectionUpdate::default(),
+        actual_branch_protection: Option<&BranchProtection>,
+    ) -> Option<BranchProtectionDiff> {
+        let mut differences = Vec::new();
+
+        let mut update = match (actual_branch_protection, &self.operation) {
+            // Handled by should_delete, there is nothing to update
+            (_, BranchProtectionOperation::MustNotExist)
+            // The repo does not contain a branch protection rule with this pattern
+            // We do not require a branch to be created
+            | (None, BranchProtectionOperation::MayExist) => return None,
+            (Some(actual_branch_protection), _) => {
+                let mut update = BranchProtectionUpdate::from(actual_branch_protection);
+                // The update replaces the whole branch protection, so settings we don't manage
+                // must be sent as they are to not reset them
+                update.required_linear_history = actual_branch_protection.required_linear_history;
+                update.allow_force_pushes = actual_branch_protection.allow_force_pushes;
+                update.allow_deletions = actual_branch_protection.allow_deletions;
+                update.block_creations = actual_branch_protection.block_creations;
+                update.required_conversation_resolution =
+                    actual_branch_protection.required_conversation_resolution;
+                update.lock_branch = actual_branch_protection.lock_branch;
+                update.allow_fork_syncing = actual_branch_protection.allow_fork_syncing;
+                update
+            }
+            (None, BranchProtectionOperation::MustExist) => {
+                differences.push("create branch protection".to_string());
+                BranchProtectionUpdate::default()
             }
         };
 
-        if let Some(v) = self.is_admin_enforced {
-            diff.enforce_admins = Some(v);
+        if let Some(desired) = self.is_admin_enforced {
+            let actual = update.enforce_admins.unwrap_or(false);
+            if desired != actual {
+                differences.push(format!("is_admin_enforced: {actual} -> {desired}"));
+            }
+            update.enforce_admins = Some(desired);
         }
-        if let Some(v) = self.allow_deletions {
-            diff.allow_deletions = Some(v);
+        if let Some(desired) = self.allow_deletions {
+            let actual = update.allow_deletions.unwrap_or(false);
+            if desired != actual {
+                differences.push(format!("allow_deletions: {actual} -> {desired}"));
+            }
+            update.allow_deletions = Some(desired);
+        }
+        if let Some(restrictions) = &self.restrictions {
+            let desired = ListOfUserTeamsOrApps::from(restrictions);
+            if update.restrictions.as_ref() != Some(&desired) {
+                differences.push(format!(
+                    "restrictions: {:?} -> {desired:?}",
+                    update.restrictions
+                ));
+            }
+            update.restrictions = Some(desired);
         }
 
-        Ok(Some(diff))
+        if let Some(constraint) = &self.required_approving_review_count {
+            // Not requiring pull request reviews is the same as requiring 0 approving reviews
+            let actual = update
+                .required_pull_request_reviews
+                .as_ref()
+                .map_or(0, |reviews| reviews.required_approving_review_count);
+            if let Some(desired) = constraint.violation(&actual) {
+                differences.push(format!(
+                    "required_approving_review_count: {actual} -> {desired}"
+                ));
+                update
+                    .required_pull_request_reviews
+                    .get_or_insert_with(Default::default)
+                    .required_approving_review_count = desired;
+            }
+        }
+
+        // Required signatures are updated through their own endpoint
+        let update = (!differences.is_empty()).then_some(update);
+
+        let required_signatures = self.required_signatures_diff(actual_branch_protection);
+        if let Some(desired) = required_signatures {
+            differences.push(format!("required_signatures: {} -> {desired}", !desired));
+        }
+
+        if differences.is_empty() {
+            return None;
+        }
+
+        Some(BranchProtectionDiff {
+            update,
+            required_signatures,
+            differences,
+        })
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct BranchProtectionDiff {
+    /// The full branch protection to send, if any of its settings differ
+    pub update: Option<BranchProtectionUpdate>,
+    /// Whether commit signatures must be required, if that differs
+    pub required_signatures: Option<bool>,
+    /// Human readable differences in the branch protection
+    pub differences: Vec<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use crate::config::load_from_reader;
+    use crate::constraint::Range;
+    use rstest::rstest;
 
     use super::*;
 
@@ -97,10 +308,267 @@ mod tests {
             operation: BranchProtectionOperation::MayExist,
             is_admin_enforced: Some(true),
             allow_deletions: None,
+            restrictions: None,
+            required_approving_review_count: None,
+            required_signatures: None,
+            required_status_checks: None,
+            custom_properties: None,
         }];
 
         assert_eq!(expected_rules, actual_rules.branch_protections.unwrap());
 
         Ok(())
     }
+
+    #[test]
+    fn test_push_restrictions_ignore_order() -> Result<(), anyhow::Error> {
+        let contents = r#"
+{"branch_protections": [
+{
+    "branch_name_pattern": "master",
+    "operation": "must_exist",
+    "restrictions": {
+        "users": ["pajlada", "zneix"],
+        "teams": ["maintainers"]
+    }
+}
+]}"#;
+        let reader = std::io::Cursor::new(contents);
+        let config = load_from_reader(reader)?;
+        let rule = &config.branch_protections.unwrap()[0];
+
+        let diff = rule.diff(None).unwrap();
+
+        assert_eq!(
+            diff.update.unwrap().restrictions,
+            Some(ListOfUserTeamsOrApps::new(
+                vec!["zneix".to_string(), "pajlada".to_string()],
+                vec!["maintainers".to_string()],
+                vec![],
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(BranchProtectionOperation::MustExist, Some(true), None, Some(true))]
+    #[case(BranchProtectionOperation::MayExist, Some(true), None, None)]
+    #[case(
+        BranchProtectionOperation::MayExist,
+        Some(true),
+        Some(false),
+        Some(true)
+    )]
+    #[case(BranchProtectionOperation::MayExist, Some(true), Some(true), None)]
+    #[case(
+        BranchProtectionOperation::MayExist,
+        Some(false),
+        Some(true),
+        Some(false)
+    )]
+    #[case(BranchProtectionOperation::MayExist, None, Some(true), None)]
+    #[case(BranchProtectionOperation::MustNotExist, Some(true), Some(false), None)]
+    fn test_required_signatures_diff(
+        #[case] operation: BranchProtectionOperation,
+        #[case] desired: Option<bool>,
+        #[case] actual: Option<bool>,
+        #[case] expected: Option<bool>,
+    ) {
+        let rule = BranchProtectionRule {
+            branch_name_pattern: "master".to_string(),
+            operation,
+            is_admin_enforced: None,
+            allow_deletions: None,
+            restrictions: None,
+            required_approving_review_count: None,
+            required_signatures: desired,
+            required_status_checks: None,
+            custom_properties: None,
+        };
+        let actual = actual.map(|required_signatures| BranchProtection {
+            required_signatures: Some(required_signatures),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            rule.diff(actual.as_ref())
+                .and_then(|diff| diff.required_signatures),
+            expected
+        );
+    }
+
+    fn team(slug: &str) -> serde_json::Value {
+        serde_json::json!({
+            "html_url": "",
+            "id": 1,
+            "members_url": "",
+            "name": slug,
+            "node_id": "",
+            "permission": "push",
+            "repositories_url": "",
+            "slug": slug,
+            "url": "",
+        })
+    }
+
+    #[test]
+    fn test_diff_compliant() -> Result<(), anyhow::Error> {
+        let actual = serde_json::json!({
+            "enforce_admins": {"enabled": true},
+            "allow_deletions": {"enabled": false},
+            "required_linear_history": {"enabled": true},
+            "required_pull_request_reviews": {
+                "dismiss_stale_reviews": false,
+                "require_code_owner_reviews": false,
+                "required_approving_review_count": 2,
+            },
+            "restrictions": {"users": [], "teams": [team("b"), team("a")], "apps": []},
+        });
+        let actual: BranchProtection = serde_json::from_str(&actual.to_string())?;
+        let mut rule = BranchProtectionRule {
+            branch_name_pattern: "master".to_string(),
+            operation: BranchProtectionOperation::MustExist,
+            is_admin_enforced: Some(true),
+            allow_deletions: Some(false),
+            restrictions: Some(PushRestrictions {
+                teams: vec!["a".to_string(), "b".to_string()],
+                ..Default::default()
+            }),
+            required_approving_review_count: Some(Constraint::Range(Range {
+                min: Some(1),
+                max: None,
+            })),
+            required_signatures: Some(false),
+            required_status_checks: None,
+            custom_properties: None,
+        };
+
+        assert_eq!(rule.diff(Some(&actual)), None);
+
+        rule.is_admin_enforced = Some(false);
+        let diff = rule.diff(Some(&actual)).unwrap();
+
+        assert_eq!(diff.differences, vec!["is_admin_enforced: true -> false"]);
+        assert_eq!(diff.required_signatures, None);
+        let update = diff.update.unwrap();
+        assert_eq!(update.enforce_admins, Some(false));
+        // Settings the rule doesn't manage are sent as they are
+        assert_eq!(update.required_linear_history, Some(true));
+
+        rule.is_admin_enforced = None;
+        rule.required_approving_review_count = Some(Constraint::Range(Range {
+            min: Some(3),
+            max: None,
+        }));
+        let diff = rule.diff(Some(&actual)).unwrap();
+
+        assert_eq!(
+            diff.differences,
+            vec!["required_approving_review_count: 2 -> 3"]
+        );
+        assert_eq!(
+            diff.update
+                .unwrap()
+                .required_pull_request_reviews
+                .unwrap()
+                .required_approving_review_count,
+            3
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_must_not_exist() {
+        let rule = BranchProtectionRule {
+            branch_name_pattern: "gh-pages".to_string(),
+            operation: BranchProtectionOperation::MustNotExist,
+            is_admin_enforced: Some(true),
+            allow_deletions: None,
+            restrictions: None,
+            required_approving_review_count: None,
+            required_signatures: None,
+            required_status_checks: None,
+            custom_properties: None,
+        };
+        let actual = BranchProtection::default();
+
+        assert!(rule.should_delete(Some(&actual)));
+        assert!(!rule.should_delete(None));
+        assert_eq!(rule.diff(Some(&actual)), None);
+        assert_eq!(rule.diff(None), None);
+    }
+
+    #[test]
+    fn test_matching_branches() -> Result<(), anyhow::Error> {
+        let rule = BranchProtectionRule {
+            branch_name_pattern: "release/*".to_string(),
+            operation: BranchProtectionOperation::MayExist,
+            is_admin_enforced: None,
+            allow_deletions: None,
+            restrictions: None,
+            required_approving_review_count: None,
+            required_signatures: None,
+            required_status_checks: None,
+            custom_properties: None,
+        };
+        let branches: Vec<Branch> = [
+            "master",
+            "release/1.0",
+            "release/2.0",
+            "release/2.0/hotfix",
+            "feature/release",
+        ]
+        .into_iter()
+        .map(|name| Branch {
+            name: name.to_string(),
+        })
+        .collect();
+
+        assert!(rule.is_glob());
+        assert_eq!(
+            rule.matching_branches(&branches)?,
+            vec!["release/1.0", "release/2.0"]
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("$default_branch", "must_exist", "main", true)]
+    #[case("$default_branch", "may_exist", "master", false)]
+    #[case("release/*", "may_exist", "release/1.0", true)]
+    #[case("release/*", "may_exist", "release/1.0/hotfix", false)]
+    #[case("main", "must_not_exist", "main", false)]
+    fn test_protects(
+        #[case] branch_name_pattern: &str,
+        #[case] operation: &str,
+        #[case] branch_name: &str,
+        #[case] expected: bool,
+    ) -> Result<(), anyhow::Error> {
+        let contents = serde_json::json!({"branch_protections": [{
+            "branch_name_pattern": branch_name_pattern,
+            "operation": operation,
+            "required_status_checks": [{"context": "build"}, {"context": "test", "app_id": 15368}],
+        }]})
+        .to_string();
+        let config = load_from_reader(std::io::Cursor::new(contents))?;
+        let rule = &config.branch_protections.unwrap()[0];
+        let repo = crate::models::Repository::for_test(crate::models::Info::for_test(
+            "pajlada/pajbot",
+            serde_json::json!({"default_branch": "main"}),
+        ));
+
+        assert_eq!(rule.protects(&repo, branch_name)?, expected);
+        assert_eq!(
+            rule.required_status_checks()
+                .iter()
+                .map(|check| (check.context.as_str(), check.app_id))
+                .collect::<Vec<_>>(),
+            vec![("build", None), ("test", Some(15368))]
+        );
+
+        Ok(())
+    }
 }
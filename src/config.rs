@@ -1,34 +1,133 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
 
 use crate::branch_protection_rules::BranchProtectionRule;
+use crate::custom_properties::CustomProperties;
+use crate::default_branch::DefaultBranch;
+use crate::deploy_keys::DeployKeys;
+use crate::environments::EnvironmentRule;
+use crate::file_sync::FileSync;
+use crate::permissions::Permissions;
 use crate::repository_settings::RepositorySettings;
-use crate::topic_operation::TopicOperations;
+use crate::status_checks::StatusChecks;
+use crate::topic_operation::{TopicOperations, TopicVocabulary};
+use crate::webhooks::Webhooks;
 
 fn default_github_api_root() -> String {
     "https://api.github.com".to_string()
 }
 
+/// Which GitHub API repositories are loaded with
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchApi {
+    /// A few requests per repository
+    #[default]
+    Rest,
+    /// One query per 100 repositories, including their topics and branch protection rules
+    Graphql,
+}
+
 #[derive(Debug, Deserialize, PartialEq, Eq)]
 pub struct Config {
     #[serde(default = "default_github_api_root")]
     pub github_api_root: String,
+    #[serde(default)]
+    pub fetch_api: FetchApi,
 
     pub settings: Option<RepositorySettings>,
+    pub default_branch: Option<DefaultBranch>,
     pub branch_protections: Option<Vec<BranchProtectionRule>>,
+    pub status_checks: Option<StatusChecks>,
     pub topics: Option<TopicOperations>,
+    pub topic_vocabulary: Option<TopicVocabulary>,
+    pub file_sync: Option<FileSync>,
+    pub permissions: Option<Permissions>,
+    pub webhooks: Option<Webhooks>,
+    pub deploy_keys: Option<DeployKeys>,
+    pub environments: Option<Vec<EnvironmentRule>>,
+    pub custom_properties: Option<CustomProperties>,
+    /// Report settings and branch protections that contradict each other
+    #[serde(default)]
+    pub consistency_checks: bool,
+}
+
+impl Config {
+    /// Whether the custom property values of the repositories are needed
+    pub fn uses_custom_properties(&self) -> bool {
+        self.custom_properties.is_some()
+            || self
+                .branch_protections
+                .iter()
+                .flatten()
+                .any(|rule| rule.custom_properties().is_some())
+            || self
+                .environments
+                .iter()
+                .flatten()
+                .any(|rule| rule.custom_properties.is_some())
+            || self.permissions.iter().any(|permissions| {
+                permissions
+                    .teams
+                    .iter()
+                    .any(|team| team.custom_properties.is_some())
+            })
+    }
+
+    /// Rejects policies GitHub would refuse to apply
+    fn validate(&self) -> Result<()> {
+        if let Some(settings) = &self.settings {
+            settings.validate().context("Invalid settings")?;
+        }
+
+        for rule in self.branch_protections.iter().flatten() {
+            rule.validate().context("Invalid branch_protections")?;
+        }
+
+        if let Some(status_checks) = &self.status_checks {
+            status_checks.validate().context("Invalid status_checks")?;
+        }
+
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.validate().context("Invalid webhooks")?;
+        }
+
+        if let Some(vocabulary) = &self.topic_vocabulary {
+            vocabulary.validate().context("Invalid topic_vocabulary")?;
+        }
+        for operation in self.topics.iter().flatten() {
+            operation
+                .validate(self.topic_vocabulary.as_ref())
+                .context("Invalid topics")?;
+        }
+
+        if let Some(file_sync) = &self.file_sync {
+            file_sync.validate().context("Invalid file_sync")?;
+        }
+
+        Ok(())
+    }
 }
 
 pub fn load_from_reader<R: std::io::Read>(reader: R) -> Result<Config> {
-    Ok(serde_json::from_reader(reader)?)
+    let config: Config = serde_json::from_reader(reader)?;
+    config.validate()?;
+
+    Ok(config)
 }
 
 pub fn load(path: &Path) -> Result<Config> {
     let file = File::open(path)?;
     let reader = BufReader::new(file);
 
-    load_from_reader(reader)
+    let mut config = load_from_reader(reader)?;
+
+    if let Some(file_sync) = &mut config.file_sync {
+        file_sync.resolve_sources(path.parent().unwrap_or(Path::new("")));
+    }
+
+    Ok(config)
 }
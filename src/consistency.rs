@@ -0,0 +1,145 @@
+use std::fmt;
+
+use crate::models::{repository, BranchProtection};
+
+/// The locations GitHub looks for a CODEOWNERS file in, in order
+pub const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// Linear history is required, but merge commits are the only allowed merge method
+    LinearHistoryWithoutSquashOrRebase,
+    /// Code owner reviews are required, but there is no CODEOWNERS file to pick reviewers from
+    CodeOwnerReviewsWithoutCodeowners,
+    /// Pull requests can be merged automatically without waiting for any status checks
+    AutoMergeWithoutRequiredChecks,
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Inconsistency::LinearHistoryWithoutSquashOrRebase => f.write_str(
+                "linear history is required, but neither squash nor rebase merging is allowed",
+            ),
+            Inconsistency::CodeOwnerReviewsWithoutCodeowners => {
+                f.write_str("code owner reviews are required, but there is no CODEOWNERS file")
+            }
+            Inconsistency::AutoMergeWithoutRequiredChecks => {
+                f.write_str("auto merge is allowed, but no status checks are required")
+            }
+        }
+    }
+}
+
+/// Whether code owner reviews are required, meaning we need to know if a CODEOWNERS file exists
+pub fn requires_code_owner_reviews(default_branch_protection: Option<&BranchProtection>) -> bool {
+    default_branch_protection
+        .and_then(|branch_protection| branch_protection.required_pull_request_reviews.as_ref())
+        .is_some_and(|reviews| reviews.require_code_owner_reviews)
+}
+
+/// Looks for settings of the repository and the protection of its default branch that contradict
+/// each other
+pub fn check(
+    info: &repository::Info,
+    default_branch_protection: Option<&BranchProtection>,
+    has_codeowners: bool,
+) -> Vec<Inconsistency> {
+    let mut inconsistencies = Vec::new();
+
+    let requires_linear_history = default_branch_protection
+        .and_then(|branch_protection| branch_protection.required_linear_history)
+        .unwrap_or(false);
+    if requires_linear_history
+        && info.allow_squash_merge != Some(true)
+        && info.allow_rebase_merge != Some(true)
+    {
+        inconsistencies.push(Inconsistency::LinearHistoryWithoutSquashOrRebase);
+    }
+
+    if requires_code_owner_reviews(default_branch_protection) && !has_codeowners {
+        inconsistencies.push(Inconsistency::CodeOwnerReviewsWithoutCodeowners);
+    }
+
+    let has_required_checks = default_branch_protection
+        .and_then(|branch_protection| branch_protection.required_status_checks.as_ref())
+        .is_some_and(|required_status_checks| {
+            !required_status_checks.checks.is_empty() || !required_status_checks.contexts.is_empty()
+        });
+    if info.allow_auto_merge == Some(true) && !has_required_checks {
+        inconsistencies.push(Inconsistency::AutoMergeWithoutRequiredChecks);
+    }
+
+    inconsistencies
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    /// `merge_methods` are merge commit, squash and rebase
+    fn info(merge_methods: [bool; 3], auto_merge: bool) -> repository::Info {
+        repository::Info::for_test(
+            "pajlada/a",
+            serde_json::json!({
+                "allow_merge_commit": merge_methods[0],
+                "allow_squash_merge": merge_methods[1],
+                "allow_rebase_merge": merge_methods[2],
+                "allow_auto_merge": auto_merge,
+            }),
+        )
+    }
+
+    fn branch_protection(
+        linear_history: bool,
+        code_owner_reviews: bool,
+        checks: &[&str],
+    ) -> BranchProtection {
+        let branch_protection = serde_json::json!({
+            "required_linear_history": {"enabled": linear_history},
+            "required_pull_request_reviews": {
+                "dismiss_stale_reviews": false,
+                "require_code_owner_reviews": code_owner_reviews,
+            },
+            "required_status_checks": {"checks": [], "contexts": checks},
+        });
+        serde_json::from_str(&branch_protection.to_string()).unwrap()
+    }
+
+    #[rstest]
+    #[case(info([true, true, false], false), None, false, vec![])]
+    #[case(
+        info([true, false, false], false),
+        Some(branch_protection(true, false, &[])),
+        false,
+        vec![Inconsistency::LinearHistoryWithoutSquashOrRebase],
+    )]
+    #[case(info([true, false, true], false), Some(branch_protection(true, false, &[])), false, vec![])]
+    #[case(
+        info([true, true, true], false),
+        Some(branch_protection(false, true, &[])),
+        false,
+        vec![Inconsistency::CodeOwnerReviewsWithoutCodeowners],
+    )]
+    #[case(info([true, true, true], false), Some(branch_protection(false, true, &[])), true, vec![])]
+    #[case(
+        info([true, true, true], true),
+        None,
+        false,
+        vec![Inconsistency::AutoMergeWithoutRequiredChecks],
+    )]
+    #[case(info([true, true, true], true), Some(branch_protection(false, false, &["ci"])), false, vec![])]
+    fn test_check(
+        #[case] info: repository::Info,
+        #[case] default_branch_protection: Option<BranchProtection>,
+        #[case] has_codeowners: bool,
+        #[case] expected: Vec<Inconsistency>,
+    ) {
+        assert_eq!(
+            check(&info, default_branch_protection.as_ref(), has_codeowners),
+            expected
+        );
+    }
+}
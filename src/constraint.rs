@@ -0,0 +1,123 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Range<T> {
+    pub min: Option<T>,
+    pub max: Option<T>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct OneOf<T> {
+    pub one_of: Vec<T>,
+}
+
+/// A constraint on a setting, e.g. `true`, `{ "min": 1 }` or `{ "one_of": ["private", "internal"] }`
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Constraint<T> {
+    Exact(T),
+    OneOf(OneOf<T>),
+    Range(Range<T>),
+}
+
+impl<T: PartialOrd + Clone> Constraint<T> {
+    pub fn is_satisfied_by(&self, actual: &T) -> bool {
+        match self {
+            Constraint::Exact(expected) => expected == actual,
+            Constraint::OneOf(OneOf { one_of }) => one_of.contains(actual),
+            Constraint::Range(Range { min, max }) => {
+                min.as_ref().is_none_or(|min| actual >= min)
+                    && max.as_ref().is_none_or(|max| actual <= max)
+            }
+        }
+    }
+
+    /// Rejects constraints that can't be satisfied or that are always satisfied
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Constraint::Exact(_) => {}
+            Constraint::OneOf(OneOf { one_of }) => {
+                if one_of.is_empty() {
+                    bail!("one_of can't be empty");
+                }
+            }
+            Constraint::Range(Range { min, max }) => match (min, max) {
+                (None, None) => bail!("a range needs a min or a max"),
+                (Some(min), Some(max)) if min > max => bail!("min can't be greater than max"),
+                _ => {}
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Returns the value the setting should be changed to, or None if the actual value already
+    /// satisfies the constraint.
+    /// The value is deterministic: the first value of `one_of`, or the closest bound of a range
+    pub fn violation(&self, actual: &T) -> Option<T> {
+        if self.is_satisfied_by(actual) {
+            return None;
+        }
+
+        match self {
+            Constraint::Exact(expected) => Some(expected.clone()),
+            Constraint::OneOf(OneOf { one_of }) => one_of.first().cloned(),
+            Constraint::Range(Range { min, max }) => match (min, max) {
+                (Some(min), _) if actual < min => Some(min.clone()),
+                (_, Some(max)) if actual > max => Some(max.clone()),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("1", 0, Some(1))]
+    #[case("1", 1, None)]
+    #[case(r#"{"min": 1}"#, 0, Some(1))]
+    #[case(r#"{"min": 1}"#, 3, None)]
+    #[case(r#"{"min": 1, "max": 2}"#, 3, Some(2))]
+    #[case(r#"{"max": 2}"#, 0, None)]
+    #[case(r#"{"one_of": [2, 4]}"#, 4, None)]
+    #[case(r#"{"one_of": [2, 4]}"#, 3, Some(2))]
+    fn test_violation(
+        #[case] constraint: &str,
+        #[case] actual: i64,
+        #[case] expected: Option<i64>,
+    ) -> anyhow::Result<()> {
+        let constraint: Constraint<i64> = serde_json::from_str(constraint)?;
+
+        assert_eq!(constraint.violation(&actual), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("1", true)]
+    #[case(r#"{"min": 1, "max": 1}"#, true)]
+    #[case(r#"{"min": 2, "max": 1}"#, false)]
+    #[case("{}", false)]
+    #[case(r#"{"one_of": [2]}"#, true)]
+    #[case(r#"{"one_of": []}"#, false)]
+    fn test_validate(#[case] constraint: &str, #[case] valid: bool) -> anyhow::Result<()> {
+        let constraint: Constraint<i64> = serde_json::from_str(constraint)?;
+
+        assert_eq!(constraint.validate().is_ok(), valid);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_constraint() {
+        assert!(serde_json::from_str::<Constraint<i64>>(r#"{"minimum": 1}"#).is_err());
+    }
+}
@@ -1,7 +1,10 @@
+use std::collections::HashMap;
+
 use console::Term;
 
 use crate::api;
 use crate::config::Config;
+use crate::models::CustomProperty;
 use crate::options::Options;
 
 pub struct Context {
@@ -9,4 +12,6 @@ pub struct Context {
     pub terminal: Term,
     pub api_client: api::Client,
     pub options: Options,
+    /// Custom properties defined by each organization, loaded when first needed
+    pub custom_property_schemas: HashMap<String, Vec<CustomProperty>>,
 }
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::models::{CustomProperty, CustomPropertyAssignment, CustomPropertyValue};
+
+/// Custom property values a repository must have for a policy to apply to it.
+/// Each property must have one of the listed values
+pub type CustomPropertySelector = HashMap<String, Vec<String>>;
+
+/// Whether the repository's custom properties match every property of the selector
+pub fn matches(
+    selector: Option<&CustomPropertySelector>,
+    properties: &HashMap<String, Vec<String>>,
+) -> bool {
+    selector.is_none_or(|selector| {
+        selector.iter().all(|(name, allowed)| {
+            properties
+                .get(name)
+                .is_some_and(|values| values.iter().any(|value| allowed.contains(value)))
+        })
+    })
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct RequiredCustomProperty {
+    pub name: String,
+    /// Defaults to the allowed values defined by the organization
+    pub allowed_values: Option<Vec<String>>,
+    /// The value set with --fix. Defaults to the default value defined by the organization, or
+    /// the first allowed value
+    pub default: Option<CustomPropertyValue>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CustomProperties {
+    #[serde(default)]
+    pub required: Vec<RequiredCustomProperty>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CustomPropertyViolation {
+    pub name: String,
+    pub actual: Vec<String>,
+    /// The value that fixes the violation, if one is known
+    pub fix: Option<CustomPropertyValue>,
+}
+
+impl CustomProperties {
+    pub fn violations(
+        &self,
+        schema: &[CustomProperty],
+        properties: &HashMap<String, Vec<String>>,
+    ) -> Vec<CustomPropertyViolation> {
+        let mut violations = Vec::new();
+
+        for required in &self.required {
+            let definition = schema
+                .iter()
+                .find(|property| property.property_name == required.name);
+            let allowed_values = required
+                .allowed_values
+                .as_ref()
+                .or(definition.and_then(|property| property.allowed_values.as_ref()));
+
+            let actual = properties.get(&required.name).cloned().unwrap_or_default();
+            let valid = !actual.is_empty()
+                && allowed_values
+                    .is_none_or(|allowed| actual.iter().all(|value| allowed.contains(value)));
+            if valid {
+                continue;
+            }
+
+            let fix = required
+                .default
+                .clone()
+                .or_else(|| definition.and_then(|property| property.default_value.clone()))
+                .or_else(|| {
+                    allowed_values
+                        .and_then(|allowed| allowed.first().cloned())
+                        .map(CustomPropertyValue::Single)
+                })
+                .map(|fix| match definition {
+                    Some(property) => fix.for_value_type(&property.value_type),
+                    None => fix,
+                });
+
+            violations.push(CustomPropertyViolation {
+                name: required.name.clone(),
+                actual,
+                fix,
+            });
+        }
+
+        violations
+    }
+}
+
+/// The repository's custom property values by property name
+pub fn by_name(assignments: Vec<CustomPropertyAssignment>) -> HashMap<String, Vec<String>> {
+    assignments
+        .into_iter()
+        .filter_map(|assignment| {
+            let values = assignment.value.as_ref().map(CustomPropertyValue::values)?;
+            Some((assignment.property_name, values))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::load_from_reader;
+
+    use super::*;
+
+    fn properties(values: &[(&str, &str)]) -> HashMap<String, Vec<String>> {
+        values
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), vec![(*value).to_string()]))
+            .collect()
+    }
+
+    #[test]
+    fn test_matches() {
+        let selector = CustomPropertySelector::from([(
+            "tier".to_string(),
+            vec!["critical".to_string(), "high".to_string()],
+        )]);
+
+        assert!(matches(None, &properties(&[])));
+        assert!(matches(Some(&selector), &properties(&[("tier", "high")])));
+        assert!(!matches(Some(&selector), &properties(&[("tier", "low")])));
+        assert!(!matches(Some(&selector), &properties(&[])));
+    }
+
+    #[test]
+    fn test_violations() -> anyhow::Result<()> {
+        let contents = r#"
+{"custom_properties": {"required": [
+    {"name": "team"},
+    {"name": "tier", "default": "low"},
+    {"name": "language", "allowed_values": ["rust", "go"]},
+    {"name": "platforms"}
+]}}"#;
+        let config = load_from_reader(std::io::Cursor::new(contents))?;
+        let custom_properties = config.custom_properties.unwrap();
+        let schema: Vec<CustomProperty> = serde_json::from_str(
+            r#"[
+                {"property_name": "team", "value_type": "string", "allowed_values": null, "default_value": "unowned"},
+                {"property_name": "tier", "value_type": "single_select", "allowed_values": ["low", "high"], "default_value": null},
+                {"property_name": "platforms", "value_type": "multi_select", "allowed_values": ["linux", "windows"], "default_value": null}
+            ]"#,
+        )?;
+
+        assert_eq!(
+            custom_properties.violations(
+                &schema,
+                &properties(&[
+                    ("team", "ops"),
+                    ("tier", "medium"),
+                    ("language", "rust"),
+                    ("platforms", "linux")
+                ])
+            ),
+            vec![CustomPropertyViolation {
+                name: "tier".to_string(),
+                actual: vec!["medium".to_string()],
+                fix: Some(CustomPropertyValue::Single("low".to_string())),
+            }]
+        );
+        assert_eq!(
+            custom_properties.violations(&schema, &properties(&[("tier", "high")])),
+            vec![
+                CustomPropertyViolation {
+                    name: "team".to_string(),
+                    actual: vec![],
+                    fix: Some(CustomPropertyValue::Single("unowned".to_string())),
+                },
+                CustomPropertyViolation {
+                    name: "language".to_string(),
+                    actual: vec![],
+                    fix: Some(CustomPropertyValue::Single("rust".to_string())),
+                },
+                // Multi select properties are set to a list of values
+                CustomPropertyViolation {
+                    name: "platforms".to_string(),
+                    actual: vec![],
+                    fix: Some(CustomPropertyValue::Multiple(vec!["linux".to_string()])),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct DefaultBranch {
+    /// The desired name of the default branch, e.g. `main`
+    pub name: String,
+}
+
+impl DefaultBranch {
+    /// Returns the desired name if the repository's default branch is named differently
+    pub fn diff<'a>(&'a self, repository: &crate::models::repository::Info) -> Option<&'a str> {
+        if repository.default_branch == self.name {
+            None
+        } else {
+            Some(&self.name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::load_from_reader;
+
+    use super::*;
+
+    #[test]
+    fn test_load_default_branch() -> anyhow::Result<()> {
+        let contents = r#"{"default_branch": {"name": "main"}}"#;
+        let reader = std::io::Cursor::new(contents);
+        let config = load_from_reader(reader)?;
+
+        assert_eq!(
+            config.default_branch,
+            Some(DefaultBranch {
+                name: "main".to_string()
+            })
+        );
+
+        Ok(())
+    }
+}
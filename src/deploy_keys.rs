@@ -0,0 +1,145 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::models::DeployKey;
+
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct DeployKeys {
+    /// Allow deploy keys with write access
+    #[serde(default)]
+    pub allow_write: bool,
+    /// Deploy keys older than this many days are disallowed
+    pub max_age_days: Option<i64>,
+    /// If set, only deploy keys with one of these titles or `allowed_fingerprints` are allowed
+    pub allowed_titles: Option<Vec<String>>,
+    /// If set, only deploy keys with one of these fingerprints or `allowed_titles` are allowed
+    pub allowed_fingerprints: Option<Vec<String>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeployKeyViolation {
+    WriteAccess,
+    TooOld { age_days: i64 },
+    NotAllowed,
+}
+
+impl fmt::Display for DeployKeyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeployKeyViolation::WriteAccess => f.write_str("has write access"),
+            DeployKeyViolation::TooOld { age_days } => write!(f, "is {age_days} days old"),
+            DeployKeyViolation::NotAllowed => f.write_str("is not in the allowlist"),
+        }
+    }
+}
+
+impl DeployKeys {
+    fn is_allowlisted(&self, key: &DeployKey) -> bool {
+        if self.allowed_titles.is_none() && self.allowed_fingerprints.is_none() {
+            return true;
+        }
+
+        if let Some(titles) = &self.allowed_titles {
+            if titles.contains(&key.title) {
+                return true;
+            }
+        }
+
+        if let Some(fingerprints) = &self.allowed_fingerprints {
+            if let Ok(fingerprint) = key.fingerprint() {
+                return fingerprints.contains(&fingerprint);
+            }
+        }
+
+        false
+    }
+
+    pub fn violations(
+        &self,
+        key: &DeployKey,
+        now: chrono::DateTime<chrono::offset::Utc>,
+    ) -> Vec<DeployKeyViolation> {
+        let mut violations = Vec::new();
+
+        if !key.read_only && !self.allow_write {
+            violations.push(DeployKeyViolation::WriteAccess);
+        }
+
+        if let Some(max_age_days) = self.max_age_days {
+            let age_days = (now - key.created_at).num_days();
+            if age_days > max_age_days {
+                violations.push(DeployKeyViolation::TooOld { age_days });
+            }
+        }
+
+        if !self.is_allowlisted(key) {
+            violations.push(DeployKeyViolation::NotAllowed);
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use rstest::rstest;
+
+    use super::*;
+
+    // ssh-keygen -l reports SHA256:icSDfjLzMBOi+oFNH3vPU4iegfwkg+yHGsO3RcOwX68 for this key
+    const KEY: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIL2R4WQpDNS9mL7ydyRdClnfFBZtTfi7P6fLNtfu+zvs";
+
+    fn key(title: &str, read_only: bool, created_at: (i32, u32, u32)) -> DeployKey {
+        DeployKey {
+            id: 1,
+            key: KEY.to_string(),
+            title: title.to_string(),
+            read_only,
+            created_at: chrono::Utc
+                .with_ymd_and_hms(created_at.0, created_at.1, created_at.2, 0, 0, 0)
+                .unwrap(),
+        }
+    }
+
+    #[rstest]
+    #[case(DeployKeys::default(), key("ci", true, (2026, 1, 1)), vec![])]
+    #[case(DeployKeys::default(), key("ci", false, (2026, 1, 1)), vec![DeployKeyViolation::WriteAccess])]
+    #[case(
+        DeployKeys { allow_write: true, ..Default::default() },
+        key("ci", false, (2026, 1, 1)),
+        vec![],
+    )]
+    #[case(
+        DeployKeys { max_age_days: Some(365), ..Default::default() },
+        key("ci", true, (2024, 1, 1)),
+        vec![DeployKeyViolation::TooOld { age_days: 731 }],
+    )]
+    #[case(
+        DeployKeys { allowed_titles: Some(vec!["ci".to_string()]), ..Default::default() },
+        key("deploy", true, (2026, 1, 1)),
+        vec![DeployKeyViolation::NotAllowed],
+    )]
+    #[case(
+        DeployKeys {
+            allowed_titles: Some(vec!["ci".to_string()]),
+            allowed_fingerprints: Some(vec![
+                "SHA256:icSDfjLzMBOi+oFNH3vPU4iegfwkg+yHGsO3RcOwX68".to_string(),
+            ]),
+            ..Default::default()
+        },
+        key("deploy", true, (2026, 1, 1)),
+        vec![],
+    )]
+    fn test_deploy_key_violations(
+        #[case] policy: DeployKeys,
+        #[case] key: DeployKey,
+        #[case] expected: Vec<DeployKeyViolation>,
+    ) {
+        let now = chrono::Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+
+        assert_eq!(policy.violations(&key, now), expected);
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::custom_properties::CustomPropertySelector;
+use crate::models::{DeploymentBranchPolicy, Environment, EnvironmentBranchPolicy, Repository};
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Reviewer {
+    /// Login of a user
+    User(String),
+    /// Slug of a team in the organization owning the repository
+    Team(String),
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentBranches {
+    /// All branches can deploy to the environment
+    All,
+    /// Only branches with branch protection rules can deploy to the environment
+    ProtectedBranches,
+    /// Only branches matching one of the given patterns can deploy to the environment.
+    /// The pattern `$default_branch` is replaced with the default branch of the repository
+    Custom(Vec<String>),
+}
+
+impl DeploymentBranches {
+    /// The deployment branch policy as sent to and returned by GitHub
+    pub fn branch_policy(&self) -> Option<EnvironmentBranchPolicy> {
+        match self {
+            DeploymentBranches::All => None,
+            DeploymentBranches::ProtectedBranches => Some(EnvironmentBranchPolicy {
+                protected_branches: true,
+                custom_branch_policies: false,
+            }),
+            DeploymentBranches::Custom(_) => Some(EnvironmentBranchPolicy {
+                protected_branches: false,
+                custom_branch_policies: true,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct EnvironmentRule {
+    pub name: String,
+    pub wait_timer: Option<u32>,
+    pub prevent_self_review: Option<bool>,
+    pub reviewers: Option<Vec<Reviewer>>,
+    pub deployment_branches: Option<DeploymentBranches>,
+    /// Only apply this rule to repositories with the given custom property values
+    pub custom_properties: Option<CustomPropertySelector>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct EnvironmentDiff {
+    /// Human readable differences in the environment's protection rules
+    pub differences: Vec<String>,
+    pub branch_policies_to_add: Vec<String>,
+    pub branch_policies_to_remove: Vec<(i64, String)>,
+}
+
+impl EnvironmentDiff {
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+            && self.branch_policies_to_add.is_empty()
+            && self.branch_policies_to_remove.is_empty()
+    }
+}
+
+fn actual_reviewers(environment: &Environment) -> HashSet<Reviewer> {
+    environment
+        .reviewers()
+        .iter()
+        .filter_map(|reviewer| match reviewer.type_.as_str() {
+            "User" => reviewer.reviewer.login.clone().map(Reviewer::User),
+            "Team" => reviewer.reviewer.slug.clone().map(Reviewer::Team),
+            _ => None,
+        })
+        .collect()
+}
+
+impl EnvironmentRule {
+    pub fn parsed_custom_branches(&self, repo: &Repository) -> Option<Vec<String>> {
+        match &self.deployment_branches {
+            Some(DeploymentBranches::Custom(patterns)) => Some(
+                patterns
+                    .iter()
+                    .map(|pattern| {
+                        if pattern == "$default_branch" {
+                            repo.info.default_branch.clone()
+                        } else {
+                            pattern.clone()
+                        }
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    pub fn diff(
+        &self,
+        repo: &Repository,
+        actual: Option<&Environment>,
+        actual_branch_policies: &[DeploymentBranchPolicy],
+    ) -> EnvironmentDiff {
+        let mut diff = EnvironmentDiff::default();
+
+        let actual_wait_timer = actual.map_or(0, Environment::wait_timer);
+        let actual_prevent_self_review = actual.is_some_and(Environment::prevent_self_review);
+        let actual_reviewers = actual.map(actual_reviewers).unwrap_or_default();
+        let actual_branch_policy = actual.and_then(|actual| actual.deployment_branch_policy);
+
+        if actual.is_none() {
+            diff.differences.push("create environment".to_string());
+        }
+
+        if let Some(wait_timer) = self.wait_timer {
+            if wait_timer != actual_wait_timer {
+                diff.differences
+                    .push(format!("wait_timer: {actual_wait_timer} -> {wait_timer}"));
+            }
+        }
+
+        if let Some(prevent_self_review) = self.prevent_self_review {
+            if prevent_self_review != actual_prevent_self_review {
+                diff.differences.push(format!(
+                    "prevent_self_review: {actual_prevent_self_review} -> {prevent_self_review}"
+                ));
+            }
+        }
+
+        if let Some(reviewers) = &self.reviewers {
+            let desired: HashSet<Reviewer> = reviewers.iter().cloned().collect();
+            if desired != actual_reviewers {
+                diff.differences.push(format!(
+                    "reviewers: add({:?}), del({:?})",
+                    desired.difference(&actual_reviewers),
+                    actual_reviewers.difference(&desired),
+                ));
+            }
+        }
+
+        if let Some(deployment_branches) = &self.deployment_branches {
+            let branch_policy = deployment_branches.branch_policy();
+            if branch_policy != actual_branch_policy {
+                diff.differences.push(format!(
+                    "deployment_branch_policy: {actual_branch_policy:?} -> {branch_policy:?}"
+                ));
+            }
+        }
+
+        if let Some(patterns) = self.parsed_custom_branches(repo) {
+            // Tag policies are not managed by the rule
+            let actual_branch_policies: Vec<&DeploymentBranchPolicy> = actual_branch_policies
+                .iter()
+                .filter(|policy| policy.is_branch())
+                .collect();
+
+            for pattern in &patterns {
+                if !actual_branch_policies.iter().any(|p| &p.name == pattern) {
+                    diff.branch_policies_to_add.push(pattern.clone());
+                }
+            }
+            for policy in actual_branch_policies {
+                if !patterns.contains(&policy.name) {
+                    diff.branch_policies_to_remove
+                        .push((policy.id, policy.name.clone()));
+                }
+            }
+        }
+
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::json;
+
+    use crate::config::load_from_reader;
+    use crate::models::Info;
+
+    use super::*;
+
+    fn repository() -> Repository {
+        Repository::for_test(Info::for_test("pajlada/pajbot", json!({})))
+    }
+
+    fn policy(id: i64, name: &str) -> DeploymentBranchPolicy {
+        DeploymentBranchPolicy {
+            id,
+            name: name.to_string(),
+            type_: "branch".to_string(),
+        }
+    }
+
+    fn tag_policy(id: i64, name: &str) -> DeploymentBranchPolicy {
+        DeploymentBranchPolicy {
+            type_: "tag".to_string(),
+            ..policy(id, name)
+        }
+    }
+
+    #[test]
+    fn test_load_environments() -> anyhow::Result<()> {
+        let contents = r#"
+{"environments": [
+    {
+        "name": "production",
+        "wait_timer": 30,
+        "prevent_self_review": true,
+        "reviewers": [{"team": "ops"}, {"user": "pajlada"}],
+        "deployment_branches": {"custom": ["$default_branch", "release/*"]}
+    },
+    {
+        "name": "staging",
+        "deployment_branches": "protected_branches"
+    }
+]}"#;
+        let reader = std::io::Cursor::new(contents);
+        let config = load_from_reader(reader)?;
+        let expected = vec![
+            EnvironmentRule {
+                name: "production".to_string(),
+                wait_timer: Some(30),
+                prevent_self_review: Some(true),
+                reviewers: Some(vec![
+                    Reviewer::Team("ops".to_string()),
+                    Reviewer::User("pajlada".to_string()),
+                ]),
+                deployment_branches: Some(DeploymentBranches::Custom(vec![
+                    "$default_branch".to_string(),
+                    "release/*".to_string(),
+                ])),
+                custom_properties: None,
+            },
+            EnvironmentRule {
+                name: "staging".to_string(),
+                wait_timer: None,
+                prevent_self_review: None,
+                reviewers: None,
+                deployment_branches: Some(DeploymentBranches::ProtectedBranches),
+                custom_properties: None,
+            },
+        ];
+
+        assert_eq!(expected, config.environments.unwrap());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_environment_protection_rules() -> anyhow::Result<()> {
+        let actual: Environment = serde_json::from_str(
+            r#"{
+            "name": "production",
+            "protection_rules": [
+                {"id": 1, "type": "wait_timer", "wait_timer": 30},
+                {"id": 2, "type": "required_reviewers", "prevent_self_review": true, "reviewers": [
+                    {"type": "Team", "reviewer": {"id": 5, "slug": "ops"}}
+                ]},
+                {"id": 3, "type": "branch_policy"}
+            ],
+            "deployment_branch_policy": {"protected_branches": true, "custom_branch_policies": false}
+        }"#,
+        )?;
+
+        assert_eq!(actual.wait_timer(), 30);
+        assert!(actual.prevent_self_review());
+        assert_eq!(
+            actual_reviewers(&actual),
+            HashSet::from([Reviewer::Team("ops".to_string())])
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::create(json!({"name": "production"}), None, &[], EnvironmentDiff {
+        differences: vec!["create environment".to_string()],
+        ..Default::default()
+    })]
+    #[case::unchanged(
+        json!({"name": "production", "wait_timer": 30, "prevent_self_review": true}),
+        Some(json!({"protection_rules": [
+            {"id": 1, "type": "wait_timer", "wait_timer": 30},
+            {"id": 2, "type": "required_reviewers", "prevent_self_review": true, "reviewers": []}
+        ]})),
+        &[],
+        EnvironmentDiff::default()
+    )]
+    #[case::wait_timer(
+        json!({"name": "production", "wait_timer": 60}),
+        Some(json!({"protection_rules": [{"id": 1, "type": "wait_timer", "wait_timer": 30}]})),
+        &[],
+        EnvironmentDiff {
+            differences: vec!["wait_timer: 30 -> 60".to_string()],
+            ..Default::default()
+        }
+    )]
+    #[case::prevent_self_review(
+        json!({"name": "production", "prevent_self_review": true}),
+        Some(json!({})),
+        &[],
+        EnvironmentDiff {
+            differences: vec!["prevent_self_review: false -> true".to_string()],
+            ..Default::default()
+        }
+    )]
+    #[case::reviewers(
+        json!({"name": "production", "reviewers": [{"team": "ops"}, {"user": "pajlada"}]}),
+        Some(json!({"protection_rules": [
+            {"id": 2, "type": "required_reviewers", "prevent_self_review": false, "reviewers": [
+                {"type": "Team", "reviewer": {"id": 5, "slug": "ops"}},
+                {"type": "User", "reviewer": {"id": 6, "login": "zneix"}}
+            ]}
+        ]})),
+        &[],
+        EnvironmentDiff {
+            differences: vec![
+                r#"reviewers: add([User("pajlada")]), del([User("zneix")])"#.to_string()
+            ],
+            ..Default::default()
+        }
+    )]
+    #[case::branch_policy(
+        json!({"name": "production", "deployment_branches": {"custom": ["$default_branch", "release/*"]}}),
+        Some(json!({"deployment_branch_policy": {"protected_branches": true, "custom_branch_policies": false}})),
+        &[policy(1, "master"), policy(2, "develop")],
+        EnvironmentDiff {
+            differences: vec![
+                "deployment_branch_policy: Some(EnvironmentBranchPolicy { protected_branches: true, custom_branch_policies: false }) -> Some(EnvironmentBranchPolicy { protected_branches: false, custom_branch_policies: true })".to_string()
+            ],
+            branch_policies_to_add: vec!["release/*".to_string()],
+            branch_policies_to_remove: vec![(2, "develop".to_string())],
+        }
+    )]
+    #[case::tag_policy(
+        json!({"name": "production", "deployment_branches": {"custom": ["v*"]}}),
+        Some(json!({"deployment_branch_policy": {"protected_branches": false, "custom_branch_policies": true}})),
+        &[tag_policy(1, "v*"), policy(2, "develop")],
+        EnvironmentDiff {
+            branch_policies_to_add: vec!["v*".to_string()],
+            branch_policies_to_remove: vec![(2, "develop".to_string())],
+            ..Default::default()
+        }
+    )]
+    #[case::all_branches(
+        json!({"name": "production", "deployment_branches": "all"}),
+        Some(json!({"deployment_branch_policy": null})),
+        &[],
+        EnvironmentDiff::default()
+    )]
+    fn test_diff(
+        #[case] rule: serde_json::Value,
+        #[case] actual: Option<serde_json::Value>,
+        #[case] actual_branch_policies: &[DeploymentBranchPolicy],
+        #[case] expected: EnvironmentDiff,
+    ) -> anyhow::Result<()> {
+        let rule: EnvironmentRule = serde_json::from_value(rule)?;
+        let actual: Option<Environment> = actual.map(serde_json::from_value).transpose()?;
+
+        assert_eq!(
+            rule.diff(&repository(), actual.as_ref(), actual_branch_policies),
+            expected
+        );
+
+        Ok(())
+    }
+}
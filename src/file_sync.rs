@@ -0,0 +1,204 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+
+use crate::models::PullRequest;
+
+fn default_branch() -> String {
+    "github-repo-lint/file-sync".to_string()
+}
+
+fn default_commit_message() -> String {
+    "Sync files with github-repo-lint".to_string()
+}
+
+fn default_pull_request_title() -> String {
+    "Sync files with github-repo-lint".to_string()
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct SyncedFile {
+    /// Path of the file in the repository, e.g. `.github/dependabot.yml`
+    pub path: String,
+    /// The canonical contents of the file
+    pub content: Option<String>,
+    /// Path to a local file containing the canonical contents of the file, relative to the
+    /// config file
+    pub source: Option<String>,
+}
+
+impl SyncedFile {
+    /// Rejects entries that do not have exactly one of `content` and `source`
+    pub fn validate(&self) -> Result<()> {
+        if self.content.is_some() == self.source.is_some() {
+            return Err(anyhow::anyhow!(
+                "File sync entry {} must have exactly one of content or source",
+                self.path
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The canonical contents as bytes, since a source file doesn't have to be text
+    pub fn desired_content(&self) -> Result<Vec<u8>> {
+        match (&self.content, &self.source) {
+            (Some(content), None) => Ok(content.clone().into_bytes()),
+            (None, Some(source)) => std::fs::read(source)
+                .with_context(|| format!("Failed to read {source} to sync to {}", self.path)),
+            _ => Err(anyhow::anyhow!(
+                "File sync entry {} must have exactly one of content or source",
+                self.path
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct FileSync {
+    /// The branch the synced files are pushed to before a pull request is opened
+    #[serde(default = "default_branch")]
+    pub branch: String,
+    #[serde(default = "default_commit_message")]
+    pub commit_message: String,
+    #[serde(default = "default_pull_request_title")]
+    pub pull_request_title: String,
+    #[serde(default)]
+    pub pull_request_body: String,
+    pub files: Vec<SyncedFile>,
+}
+
+impl FileSync {
+    pub fn validate(&self) -> Result<()> {
+        for file in &self.files {
+            file.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Makes the relative source paths relative to `config_dir` instead of the working directory
+    pub fn resolve_sources(&mut self, config_dir: &Path) {
+        for file in &mut self.files {
+            if let Some(source) = &mut file.source {
+                *source = config_dir.join(&*source).to_string_lossy().into_owned();
+            }
+        }
+    }
+}
+
+/// The SHA git gives a blob with the given contents, as returned by the contents API
+pub fn git_blob_sha(content: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", content.len()));
+    hasher.update(content);
+
+    format!("{:x}", hasher.finalize())
+}
+
+/// A file whose contents in the repository differ from the canonical contents
+#[derive(Debug, PartialEq, Eq)]
+pub struct FileChange<'a> {
+    pub path: &'a str,
+    pub content: Vec<u8>,
+    /// false if the file does not exist in the repository yet
+    pub exists: bool,
+}
+
+#[derive(Debug)]
+pub enum FileSyncResult {
+    Opened(PullRequest),
+    Updated(PullRequest),
+    UpToDate(PullRequest),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::load_from_reader;
+
+    use super::*;
+
+    #[test]
+    fn test_load_file_sync() -> Result<()> {
+        let contents = r#"
+{"file_sync": {
+    "files": [
+        {
+            "path": ".github/dependabot.yml",
+            "content": "version: 2\n"
+        }
+    ]
+}}"#;
+        let reader = std::io::Cursor::new(contents);
+        let config = load_from_reader(reader)?;
+        let expected = FileSync {
+            branch: "github-repo-lint/file-sync".to_string(),
+            commit_message: "Sync files with github-repo-lint".to_string(),
+            pull_request_title: "Sync files with github-repo-lint".to_string(),
+            pull_request_body: String::new(),
+            files: vec![SyncedFile {
+                path: ".github/dependabot.yml".to_string(),
+                content: Some("version: 2\n".to_string()),
+                source: None,
+            }],
+        };
+
+        assert_eq!(expected, config.file_sync.unwrap());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_file_sync_requires_one_source() {
+        for file in [
+            r#"{"path": "README.md"}"#,
+            r#"{"path": "README.md", "content": "hello", "source": "README.md"}"#,
+        ] {
+            let contents = format!(r#"{{"file_sync": {{"files": [{file}]}}}}"#);
+            let error = format!(
+                "{:#}",
+                load_from_reader(std::io::Cursor::new(contents)).unwrap_err()
+            );
+            assert!(
+                error.contains("exactly one of content or source"),
+                "{error}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_git_blob_sha() {
+        // git hash-object of a file containing "hello\n"
+        assert_eq!(
+            git_blob_sha(b"hello\n"),
+            "ce013625030ba8dba906f756967f9e9ca394464a"
+        );
+    }
+
+    #[test]
+    fn test_resolve_sources() -> Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("github-repo-lint-file-sync-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("templates"))?;
+        std::fs::write(dir.join("templates/dependabot.yml"), "version: 2\n")?;
+
+        let mut file_sync: FileSync = serde_json::from_str(
+            r#"{"files": [
+                {"path": ".github/dependabot.yml", "source": "templates/dependabot.yml"},
+                {"path": "LICENSE", "source": "templates/missing"}
+            ]}"#,
+        )?;
+        file_sync.resolve_sources(&dir);
+
+        assert_eq!(file_sync.files[0].desired_content()?, b"version: 2\n");
+        let error = format!("{:#}", file_sync.files[1].desired_content().unwrap_err());
+        assert!(error.contains("templates/missing"), "{error}");
+        assert!(error.contains("LICENSE"), "{error}");
+
+        std::fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+}
@@ -2,11 +2,13 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::cargo)]
 #![allow(clippy::struct_excessive_bools)]
+#![allow(clippy::multiple_crate_versions)]
 
 #[allow(unused_imports)]
 use tracing::{debug, info};
 
-use clap::{Arg, ArgAction, Command};
+use anyhow::{Context, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use console::Term;
 use reqwest::{
     blocking::Client,
@@ -14,28 +16,37 @@ use reqwest::{
 };
 
 use const_format::formatcp;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 mod api;
 mod app;
 mod branch_protection_rules;
 mod config;
+mod consistency;
+mod constraint;
 mod context;
+mod custom_properties;
+mod default_branch;
+mod deploy_keys;
+mod environments;
+mod file_sync;
 mod models;
 mod options;
+mod permissions;
+mod repository_list;
 mod repository_settings;
+mod status_checks;
+mod targets;
 mod topic_operation;
+mod webhooks;
 
 const PKG_NAME: &str = env!("CARGO_PKG_NAME");
 const PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
 const USER_AGENT: &str = formatcp!("{}/{}", PKG_NAME, PKG_VERSION);
 
-fn main() -> Result<(), anyhow::Error> {
-    tracing_subscriber::fmt::init();
-
-    let terminal = Term::stdout();
-
-    let matches = Command::new(clap::crate_name!())
+fn cli() -> Command {
+    Command::new(clap::crate_name!())
         .version(clap::crate_version!())
         .author(clap::crate_authors!())
         .about(clap::crate_description!())
@@ -53,12 +64,29 @@ fn main() -> Result<(), anyhow::Error> {
                 .action(ArgAction::SetTrue)
                 .help("Try to fix the issues found"),
         )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .short('y')
+                .action(ArgAction::SetTrue)
+                .help("Don't ask for confirmation before destructive fixes"),
+        )
         .arg(
             Arg::new("repo")
                 .long("repo")
+                .value_name("OWNER/NAME")
                 .action(ArgAction::Append)
+                .value_parser(|repo: &str| repository_list::validate(repo).map(|()| repo.to_string()))
                 .help("Target GitHub repository"),
         )
+        .arg(
+            Arg::new("repos-from")
+                .long("repos-from")
+                .value_name("FILE")
+                .help(
+                    "Target the GitHub repositories listed in a file, or - to read them from stdin",
+                ),
+        )
         .arg(
             Arg::new("user")
                 .long("user")
@@ -72,23 +100,199 @@ fn main() -> Result<(), anyhow::Error> {
                 .action(ArgAction::Append)
                 .help("Target GitHub organization"),
         )
-        .get_matches();
-
-    let repos: Vec<&str> = matches
-        .get_many::<String>("repo")
-        .unwrap_or_default()
-        .map(String::as_str)
-        .collect();
-    let users: Vec<&str> = matches
-        .get_many::<String>("user")
-        .unwrap_or_default()
-        .map(String::as_str)
-        .collect();
-    let organizations: Vec<&str> = matches
-        .get_many::<String>("organization")
-        .unwrap_or_default()
-        .map(String::as_str)
-        .collect();
+        .arg(
+            Arg::new("all-organizations")
+                .long("all-organizations")
+                .action(ArgAction::SetTrue)
+                .help("Target every organization of the GitHub Enterprise Server instance"),
+        )
+        .arg(
+            Arg::new("team")
+                .long("team")
+                .value_name("ORG/SLUG")
+                .action(ArgAction::Append)
+                .value_parser(|team: &str| {
+                    team.split_once('/')
+                        .filter(|(organization, slug)| !organization.is_empty() && !slug.is_empty())
+                        .map(|(organization, slug)| (organization.to_string(), slug.to_string()))
+                        .ok_or("expected org/slug")
+                })
+                .help("Target the repositories a GitHub team has access to"),
+        )
+        .arg(
+            Arg::new("search")
+                .long("search")
+                .value_name("QUERY")
+                .action(ArgAction::Append)
+                .help("Target the repositories matching a GitHub search query"),
+        )
+        .arg(
+            Arg::new("installation")
+                .long("installation")
+                .action(ArgAction::SetTrue)
+                .help("Target the repositories the GitHub App installation the token belongs to can access"),
+        )
+        .arg(Arg::new("me").long("me").action(ArgAction::SetTrue).help(
+            "Target the repositories of the authenticated user, including private repositories",
+        ))
+        .arg(
+            Arg::new("affiliation")
+                .long("affiliation")
+                .requires("me")
+                .value_delimiter(',')
+                .action(ArgAction::Append)
+                .value_parser(["owner", "collaborator", "organization_member"])
+                .help("Only target repositories of the authenticated user with this affiliation"),
+        )
+        .args(filter_args())
+        .args(cache_args())
+}
+
+/// Arguments for the on-disk cache of API responses
+fn cache_args() -> [Arg; 3] {
+    [
+        Arg::new("no-cache")
+            .long("no-cache")
+            .action(ArgAction::SetTrue)
+            .help("Don't cache API responses"),
+        Arg::new("cache-dir")
+            .long("cache-dir")
+            .value_name("DIR")
+            .value_parser(clap::value_parser!(PathBuf))
+            .help(
+                "Directory to cache API responses in [default: $XDG_CACHE_HOME/github-repo-lint]",
+            ),
+        Arg::new("cache-expiry")
+            .long("cache-expiry")
+            .value_name("DAYS")
+            .value_parser(clap::value_parser!(u32))
+            .default_value("7")
+            .help("Remove cached API responses after this many days"),
+    ]
+}
+
+/// Arguments narrowing down the repositories selected by the targets
+fn filter_args() -> [Arg; 7] {
+    [
+        Arg::new("visibility")
+            .long("visibility")
+            .value_parser(["public", "private", "internal"])
+            .help("Only target repositories with this visibility"),
+        Arg::new("include")
+            .long("include")
+            .value_name("GLOB")
+            .action(ArgAction::Append)
+            .value_parser(glob::Pattern::new)
+            .help("Only target repositories whose name (or owner/name, if the pattern contains a /) matches one of these patterns"),
+        Arg::new("exclude")
+            .long("exclude")
+            .value_name("GLOB")
+            .action(ArgAction::Append)
+            .value_parser(glob::Pattern::new)
+            .help("Skip repositories whose name (or owner/name, if the pattern contains a /) matches one of these patterns"),
+        Arg::new("topic")
+            .long("topic")
+            .action(ArgAction::Append)
+            .help("Only target repositories with all of these topics"),
+        Arg::new("skip-forks")
+            .long("skip-forks")
+            .action(ArgAction::SetTrue)
+            .help("Skip forked repositories"),
+        Arg::new("include-archived")
+            .long("include-archived")
+            .action(ArgAction::SetTrue)
+            .help("Check archived repositories instead of skipping them. Archived repositories are read only, so their issues can't be fixed"),
+        Arg::new("owner-type")
+            .long("owner-type")
+            .value_parser(["user", "organization"])
+            .help("Only target repositories owned by a user or an organization"),
+    ]
+}
+
+/// Opens the cache of API responses, unless caching is disabled
+fn cache(matches: &ArgMatches, github_api_token: &str) -> Result<Option<api::Cache>> {
+    if matches.get_flag("no-cache") {
+        return Ok(None);
+    }
+
+    let Some(cache_dir) = matches
+        .get_one::<PathBuf>("cache-dir")
+        .cloned()
+        .or_else(api::Cache::default_dir)
+    else {
+        info!("Not caching API responses, no cache directory found");
+        return Ok(None);
+    };
+
+    let expiry =
+        chrono::TimeDelta::days(i64::from(*matches.get_one::<u32>("cache-expiry").unwrap()));
+
+    let cache = api::Cache::new(cache_dir.clone(), expiry, github_api_token)
+        .with_context(|| format!("Failed to open cache {}", cache_dir.display()))?;
+
+    Ok(Some(cache))
+}
+
+fn main() -> Result<(), anyhow::Error> {
+    tracing_subscriber::fmt::init();
+
+    let terminal = Term::stdout();
+
+    let matches = cli().get_matches();
+
+    let values = |id: &str| -> Vec<String> {
+        matches
+            .get_many::<String>(id)
+            .unwrap_or_default()
+            .cloned()
+            .collect()
+    };
+
+    let patterns = |id: &str| -> Vec<glob::Pattern> {
+        matches
+            .get_many::<glob::Pattern>(id)
+            .unwrap_or_default()
+            .cloned()
+            .collect()
+    };
+    let visibility = matches.get_one::<String>("visibility").cloned();
+
+    let targets = targets::Targets {
+        repos: values("repo"),
+        listed_repos: match matches.get_one::<String>("repos-from") {
+            Some(path) => repository_list::read(path)?,
+            None => Vec::new(),
+        },
+        users: values("user"),
+        organizations: values("organization"),
+        all_organizations: matches.get_flag("all-organizations"),
+        teams: matches
+            .get_many::<(String, String)>("team")
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        searches: values("search"),
+        installation: matches.get_flag("installation"),
+        me: matches
+            .get_flag("me")
+            .then(|| targets::AuthenticatedUserFilter {
+                affiliation: values("affiliation"),
+                // Internal repositories are filtered afterwards, since this endpoint doesn't
+                // support them
+                visibility: visibility
+                    .clone()
+                    .filter(|visibility| visibility != "internal"),
+            }),
+        filter: targets::RepositoryFilter {
+            include: patterns("include"),
+            exclude: patterns("exclude"),
+            topics: values("topic"),
+            visibility,
+            skip_forks: matches.get_flag("skip-forks"),
+            include_archived: matches.get_flag("include-archived"),
+            owner_type: matches.get_one::<String>("owner-type").cloned(),
+        },
+    };
 
     let config_path = Path::new(matches.get_one::<String>("config").unwrap());
 
@@ -121,11 +325,17 @@ fn main() -> Result<(), anyhow::Error> {
         .default_headers(default_headers)
         .build()?;
 
-    let api_client = api::new(client, config.github_api_root.as_str())?;
+    let mut api_client = api::new(client, config.github_api_root.as_str())?;
+
+    if let Some(cache) = cache(&matches, &github_api_token)? {
+        api_client = api_client.with_cache(cache);
+    }
 
     let options = options::Options {
         dry_run: !matches.get_flag("fix"),
         dry_run_bpr: !matches.get_flag("fix"),
+        assume_yes: matches.get_flag("yes"),
+        include_archived: matches.get_flag("include-archived"),
     };
 
     let ctx = context::Context {
@@ -133,11 +343,12 @@ fn main() -> Result<(), anyhow::Error> {
         terminal,
         api_client,
         options,
+        custom_property_schemas: HashMap::new(),
     };
 
     // info!("Config: {:?}", ctx.config.topics);
 
-    app::run(ctx, repos, users, organizations)?;
+    app::run(ctx, &targets)?;
 
     // terminal.write_line("")?;
 
@@ -6,24 +6,28 @@ use serde::{Deserialize, Serialize};
 #[builder(default)]
 pub struct BranchProtection {
     #[serde(
+        default,
         skip_serializing_if = "Option::is_none",
         deserialize_with = "optionally_enabled"
     )]
     pub allow_deletions: Option<bool>,
 
     #[serde(
+        default,
         skip_serializing_if = "Option::is_none",
         deserialize_with = "optionally_enabled"
     )]
     pub allow_force_pushes: Option<bool>,
 
     #[serde(
+        default,
         skip_serializing_if = "Option::is_none",
         deserialize_with = "optionally_enabled"
     )]
     pub allow_fork_syncing: Option<bool>,
 
     #[serde(
+        default,
         skip_serializing_if = "Option::is_none",
         deserialize_with = "optionally_enabled"
     )]
@@ -33,12 +37,14 @@ pub struct BranchProtection {
     pub enabled: Option<bool>,
 
     #[serde(
+        default,
         skip_serializing_if = "Option::is_none",
         deserialize_with = "optionally_enabled"
     )]
     pub enforce_admins: Option<bool>,
 
     #[serde(
+        default,
         skip_serializing_if = "Option::is_none",
         deserialize_with = "optionally_enabled"
     )]
@@ -48,12 +54,14 @@ pub struct BranchProtection {
     pub name: Option<String>,
 
     #[serde(
+        default,
         skip_serializing_if = "Option::is_none",
         deserialize_with = "optionally_enabled"
     )]
     pub required_conversation_resolution: Option<bool>,
 
     #[serde(
+        default,
         skip_serializing_if = "Option::is_none",
         deserialize_with = "optionally_enabled"
     )]
@@ -63,6 +71,7 @@ pub struct BranchProtection {
     pub required_pull_request_reviews: Option<ProtectedBranchPullRequestReview>,
 
     #[serde(
+        default,
         skip_serializing_if = "Option::is_none",
         deserialize_with = "optionally_enabled"
     )]
@@ -1,6 +1,8 @@
 use serde::Serialize;
 
-use super::{BranchProtection, ProtectedBranchRequiredStatusCheckChecksItem};
+use super::{
+    BranchProtection, IncomingUsersTeamsOrApps, ProtectedBranchRequiredStatusCheckChecksItem,
+};
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
 pub struct RequiredStatusCheck {
@@ -24,38 +26,63 @@ pub struct RequiredStatusChecks {
     checks: Vec<RequiredStatusCheck>,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+/// User logins, team slugs and app slugs.
+/// The lists are kept sorted so that comparisons don't depend on the order GitHub returns them in
+#[derive(Default, Debug, Clone, Serialize, PartialEq, Eq)]
 pub struct ListOfUserTeamsOrApps {
     users: Vec<String>,
     teams: Vec<String>,
     apps: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+fn sorted(mut v: Vec<String>) -> Vec<String> {
+    v.sort();
+    v.dedup();
+    v
+}
+
+impl ListOfUserTeamsOrApps {
+    pub fn new(users: Vec<String>, teams: Vec<String>, apps: Vec<String>) -> Self {
+        Self {
+            users: sorted(users),
+            teams: sorted(teams),
+            apps: sorted(apps),
+        }
+    }
+}
+
+impl From<&IncomingUsersTeamsOrApps> for ListOfUserTeamsOrApps {
+    fn from(incoming: &IncomingUsersTeamsOrApps) -> Self {
+        Self::new(
+            incoming.users.iter().map(|u| u.login.clone()).collect(),
+            incoming.teams.iter().map(|t| t.slug.clone()).collect(),
+            incoming
+                .apps
+                .iter()
+                .filter_map(|a| a.slug.clone())
+                .collect(),
+        )
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, PartialEq, Eq)]
 pub struct RequiredPullRequestReviews {
     dismissal_restrictions: ListOfUserTeamsOrApps,
-    strict: bool,
     dismiss_stale_reviews: bool,
-    require_codeowner_reviews: bool,
+    require_code_owner_reviews: bool,
     // 0 = disable, 1-6 are valid
-    required_approving_review_count: i64,
+    pub required_approving_review_count: i64,
     require_last_push_approval: bool,
     bypass_pull_request_allowances: ListOfUserTeamsOrApps,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
-pub struct Restrictions {
-    // TODO: Fix
-    strict: bool,
-    checks: Vec<RequiredStatusCheck>,
-}
-
 #[derive(Default, Debug, Clone, Serialize, PartialEq, Eq)]
 pub struct BranchProtectionUpdate {
     pub required_status_checks: Option<RequiredStatusChecks>,
     pub enforce_admins: Option<bool>,
     pub required_pull_request_reviews: Option<RequiredPullRequestReviews>,
-    pub restrictions: Option<Restrictions>,
+    /// Who can push to the branch. None means everyone with push access can push
+    pub restrictions: Option<ListOfUserTeamsOrApps>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub required_linear_history: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -90,18 +117,26 @@ impl From<&BranchProtection> for BranchProtectionUpdate {
                 .required_pull_request_reviews
                 .as_ref()
                 .map(|required_pull_request_reviews| RequiredPullRequestReviews {
-                    dismissal_restrictions: todo!(),
-                    strict: todo!(),
-                    dismiss_stale_reviews: todo!(),
-                    require_codeowner_reviews: todo!(),
+                    dismissal_restrictions: required_pull_request_reviews
+                        .dismissal_restrictions
+                        .as_ref()
+                        .map(Into::into)
+                        .unwrap_or_default(),
+                    dismiss_stale_reviews: required_pull_request_reviews.dismiss_stale_reviews,
+                    require_code_owner_reviews: required_pull_request_reviews
+                        .require_code_owner_reviews,
                     required_approving_review_count: required_pull_request_reviews
                         .required_approving_review_count
                         .unwrap_or(0),
                     require_last_push_approval: required_pull_request_reviews
                         .require_last_push_approval,
-                    bypass_pull_request_allowances: todo!(),
+                    bypass_pull_request_allowances: required_pull_request_reviews
+                        .bypass_pull_request_allowances
+                        .as_ref()
+                        .map(Into::into)
+                        .unwrap_or_default(),
                 }),
-            restrictions: None,
+            restrictions: branch_protection.restrictions.as_ref().map(Into::into),
 
             // This is not fetched from branch_protection because if it shouldn't be changed,
             // it should be left as None
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct CollaboratorPermissions {
+    pub admin: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Collaborator {
+    pub login: String,
+    pub permissions: Option<CollaboratorPermissions>,
+}
+
+impl Collaborator {
+    pub fn is_admin(&self) -> bool {
+        self.permissions.as_ref().is_some_and(|p| p.admin)
+    }
+}
@@ -0,0 +1,32 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Commit {
+    pub sha: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommitStatus {
+    pub context: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CombinedCommitStatus {
+    pub statuses: Vec<CommitStatus>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckRunApp {
+    pub id: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckRun {
+    pub name: String,
+    pub app: Option<CheckRunApp>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckRuns {
+    pub check_runs: Vec<CheckRun>,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+/// A file as returned by the contents API. Its content isn't used, since GitHub leaves it out
+/// for files over 1 MB
+#[derive(Debug, Deserialize)]
+pub struct FileContents {
+    /// The git blob SHA of the file
+    pub sha: String,
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// A custom property value is a single string, or a list of strings for multi select properties
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum CustomPropertyValue {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl CustomPropertyValue {
+    pub fn values(&self) -> Vec<String> {
+        match self {
+            CustomPropertyValue::Single(value) => vec![value.clone()],
+            CustomPropertyValue::Multiple(values) => values.clone(),
+        }
+    }
+
+    /// The value in the shape GitHub expects for the given property type: a list for multi
+    /// select properties, and a single string otherwise
+    #[must_use]
+    pub fn for_value_type(self, value_type: &str) -> Self {
+        match (self, value_type) {
+            (CustomPropertyValue::Single(value), "multi_select") => {
+                CustomPropertyValue::Multiple(vec![value])
+            }
+            (CustomPropertyValue::Multiple(values), value_type)
+                if value_type != "multi_select" && values.len() == 1 =>
+            {
+                CustomPropertyValue::Single(values.into_iter().next().unwrap_or_default())
+            }
+            (value, _) => value,
+        }
+    }
+}
+
+/// A custom property defined by an organization
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomProperty {
+    pub property_name: String,
+    /// `string`, `single_select`, `multi_select`, `true_false` or `url`
+    pub value_type: String,
+    pub allowed_values: Option<Vec<String>>,
+    pub default_value: Option<CustomPropertyValue>,
+}
+
+/// The value of a custom property on a repository
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CustomPropertyAssignment {
+    pub property_name: String,
+    pub value: Option<CustomPropertyValue>,
+}
@@ -0,0 +1,53 @@
+use serde::{de, Deserialize, Deserializer};
+
+fn parse<E: de::Error>(pattern: &str) -> Result<glob::Pattern, E> {
+    glob::Pattern::new(pattern)
+        .map_err(|e| de::Error::custom(format!("invalid glob pattern '{pattern}': {e}")))
+}
+
+pub(crate) fn glob_pattern<'de, D>(deserializer: D) -> Result<Option<glob::Pattern>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(pattern) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    parse(&pattern).map(Some)
+}
+
+pub(crate) fn required_glob_pattern<'de, D>(deserializer: D) -> Result<glob::Pattern, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    parse(&String::deserialize(deserializer)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_pattern;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug)]
+    pub struct GlobPatternTest {
+        #[serde(default, deserialize_with = "glob_pattern")]
+        v: Option<glob::Pattern>,
+    }
+
+    #[test]
+    fn test_glob_pattern() -> anyhow::Result<()> {
+        let actual: GlobPatternTest = serde_json::from_str(r#"{"v":"release/*"}"#)?;
+        assert!(actual.v.unwrap().matches("release/1.0"));
+
+        let actual: GlobPatternTest = serde_json::from_str(r#"{"v":null}"#)?;
+        assert!(actual.v.is_none());
+
+        let actual: GlobPatternTest = serde_json::from_str("{}")?;
+        assert!(actual.v.is_none());
+
+        let r: serde_json::Result<GlobPatternTest> = serde_json::from_str(r#"{"v":"[a"}"#);
+        assert!(r.is_err());
+
+        Ok(())
+    }
+}
@@ -1,2 +1,6 @@
+pub(crate) mod glob_pattern;
 pub(crate) mod optionally_enabled;
+pub(crate) mod regex;
+pub(crate) use glob_pattern::*;
 pub(crate) use optionally_enabled::*;
+pub(crate) use regex::*;
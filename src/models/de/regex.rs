@@ -0,0 +1,11 @@
+use serde::{de, Deserialize, Deserializer};
+
+pub(crate) fn regex<'de, D>(deserializer: D) -> Result<regex::Regex, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let pattern = String::deserialize(deserializer)?;
+
+    regex::Regex::new(&pattern)
+        .map_err(|e| de::Error::custom(format!("invalid regex '{pattern}': {e}")))
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use base64::Engine;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Deserialize)]
+pub struct DeployKey {
+    pub id: i64,
+    /// The public key, e.g. `ssh-ed25519 AAAA...`
+    pub key: String,
+    pub title: String,
+    pub read_only: bool,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+impl DeployKey {
+    /// The SHA256 fingerprint of the key, formatted like `ssh-keygen -l` does (`SHA256:...`)
+    pub fn fingerprint(&self) -> Result<String> {
+        let blob = self
+            .key
+            .split_whitespace()
+            .nth(1)
+            .ok_or_else(|| anyhow::anyhow!("Malformed deploy key {}", self.title))?;
+
+        let bytes = base64::engine::general_purpose::STANDARD.decode(blob)?;
+        let digest = Sha256::digest(bytes);
+
+        Ok(format!(
+            "SHA256:{}",
+            base64::engine::general_purpose::STANDARD_NO_PAD.encode(digest)
+        ))
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnvironmentReviewerAccount {
+    pub id: i64,
+    /// Set for users
+    pub login: Option<String>,
+    /// Set for teams
+    pub slug: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnvironmentReviewer {
+    /// `User` or `Team`
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub reviewer: EnvironmentReviewerAccount,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EnvironmentProtectionRule {
+    WaitTimer {
+        wait_timer: u32,
+    },
+    RequiredReviewers {
+        #[serde(default)]
+        prevent_self_review: bool,
+        #[serde(default)]
+        reviewers: Vec<EnvironmentReviewer>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub struct EnvironmentBranchPolicy {
+    pub protected_branches: bool,
+    pub custom_branch_policies: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Environment {
+    #[serde(default)]
+    pub protection_rules: Vec<EnvironmentProtectionRule>,
+    /// None means all branches can deploy to the environment
+    pub deployment_branch_policy: Option<EnvironmentBranchPolicy>,
+}
+
+impl Environment {
+    pub fn wait_timer(&self) -> u32 {
+        self.protection_rules
+            .iter()
+            .find_map(|rule| match rule {
+                EnvironmentProtectionRule::WaitTimer { wait_timer } => Some(*wait_timer),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    pub fn prevent_self_review(&self) -> bool {
+        self.protection_rules.iter().any(|rule| {
+            matches!(
+                rule,
+                EnvironmentProtectionRule::RequiredReviewers {
+                    prevent_self_review: true,
+                    ..
+                }
+            )
+        })
+    }
+
+    pub fn reviewers(&self) -> &[EnvironmentReviewer] {
+        self.protection_rules
+            .iter()
+            .find_map(|rule| match rule {
+                EnvironmentProtectionRule::RequiredReviewers { reviewers, .. } => {
+                    Some(reviewers.as_slice())
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn default_deployment_policy_type() -> String {
+    "branch".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeploymentBranchPolicy {
+    pub id: i64,
+    pub name: String,
+    /// `branch` or `tag`. Older GitHub Enterprise Server versions only have branch policies
+    #[serde(rename = "type", default = "default_deployment_policy_type")]
+    pub type_: String,
+}
+
+impl DeploymentBranchPolicy {
+    pub fn is_branch(&self) -> bool {
+        self.type_ == "branch"
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeploymentBranchPolicies {
+    pub branch_policies: Vec<DeploymentBranchPolicy>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnvironmentReviewerUpdate {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub id: i64,
+}
+
+/// Body of the create or update environment request. All fields are always sent since the
+/// request replaces the protection rules of the environment
+#[derive(Debug, Serialize)]
+pub struct EnvironmentUpdate {
+    pub wait_timer: u32,
+    pub prevent_self_review: bool,
+    pub reviewers: Vec<EnvironmentReviewerUpdate>,
+    pub deployment_branch_policy: Option<EnvironmentBranchPolicy>,
+}
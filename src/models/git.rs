@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct GitObject {
+    pub sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitRef {
+    pub object: GitObject,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitCommit {
+    pub tree: GitObject,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GitTreeEntry<'a> {
+    pub path: &'a str,
+    pub mode: &'a str,
+    #[serde(rename = "type")]
+    pub type_: &'a str,
+    /// SHA of a blob created through the blobs API
+    pub sha: &'a str,
+}
+
+impl<'a> GitTreeEntry<'a> {
+    pub fn file(path: &'a str, sha: &'a str) -> Self {
+        Self {
+            path,
+            mode: "100644",
+            type_: "blob",
+            sha,
+        }
+    }
+}
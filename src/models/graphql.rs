@@ -0,0 +1,253 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Deserialize;
+
+use crate::models::{
+    BranchProtection, Info, LoadedBranchProtection, ProtectedBranchPullRequestReview,
+    ProtectedBranchRequiredStatusCheck, ProtectedBranchRequiredStatusCheckChecksItem, Repository,
+    RepositoryOwner, Topics,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct GraphQLResponse<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphQLError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GraphQLError {
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Name {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Nodes<T> {
+    pub nodes: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInfo {
+    pub has_next_page: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TotalCount {
+    pub total_count: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GraphQLRepositoryOwner {
+    pub login: String,
+    #[serde(rename = "__typename")]
+    pub typename: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepositoryTopic {
+    pub topic: Name,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchProtectionRules {
+    pub page_info: PageInfo,
+    pub nodes: Vec<GraphQLBranchProtectionRule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequiredStatusCheck {
+    pub context: String,
+    pub app: Option<App>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct App {
+    pub database_id: Option<i64>,
+}
+
+/// A branch protection rule as returned by the GraphQL API
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_field_names)]
+pub struct GraphQLBranchProtectionRule {
+    pub pattern: String,
+    pub is_admin_enforced: bool,
+    pub allows_deletions: bool,
+    pub allows_force_pushes: bool,
+    pub blocks_creations: bool,
+    pub lock_branch: bool,
+    pub lock_allows_fetch_and_merge: bool,
+    pub requires_linear_history: bool,
+    pub requires_conversation_resolution: bool,
+    pub requires_commit_signatures: bool,
+    pub requires_approving_reviews: bool,
+    pub required_approving_review_count: Option<i64>,
+    pub dismisses_stale_reviews: bool,
+    pub requires_code_owner_reviews: bool,
+    pub require_last_push_approval: bool,
+    pub requires_status_checks: bool,
+    pub requires_strict_status_checks: bool,
+    #[serde(default)]
+    pub required_status_checks: Vec<RequiredStatusCheck>,
+    pub restricts_pushes: bool,
+    pub restricts_review_dismissals: bool,
+    pub bypass_pull_request_allowances: TotalCount,
+}
+
+impl GraphQLBranchProtectionRule {
+    /// Converts the rule to the branch protection the REST API returns for matching branches.
+    /// Returns None if the rule references users, teams or apps, which the REST API returns
+    /// in full and would otherwise be reset when updating the branch protection
+    fn branch_protection(&self) -> Option<BranchProtection> {
+        if self.restricts_pushes
+            || self.restricts_review_dismissals
+            || self.bypass_pull_request_allowances.total_count > 0
+        {
+            return None;
+        }
+
+        Some(BranchProtection {
+            allow_deletions: Some(self.allows_deletions),
+            allow_force_pushes: Some(self.allows_force_pushes),
+            allow_fork_syncing: Some(self.lock_allows_fetch_and_merge),
+            block_creations: Some(self.blocks_creations),
+            enforce_admins: Some(self.is_admin_enforced),
+            lock_branch: Some(self.lock_branch),
+            required_conversation_resolution: Some(self.requires_conversation_resolution),
+            required_linear_history: Some(self.requires_linear_history),
+            required_signatures: Some(self.requires_commit_signatures),
+            required_pull_request_reviews: self.requires_approving_reviews.then_some(
+                ProtectedBranchPullRequestReview {
+                    bypass_pull_request_allowances: None,
+                    dismiss_stale_reviews: self.dismisses_stale_reviews,
+                    dismissal_restrictions: None,
+                    require_code_owner_reviews: self.requires_code_owner_reviews,
+                    require_last_push_approval: self.require_last_push_approval,
+                    required_approving_review_count: self.required_approving_review_count,
+                },
+            ),
+            required_status_checks: self.requires_status_checks.then(|| {
+                ProtectedBranchRequiredStatusCheck {
+                    checks: self
+                        .required_status_checks
+                        .iter()
+                        .map(|check| ProtectedBranchRequiredStatusCheckChecksItem {
+                            app_id: check.app.as_ref().and_then(|app| app.database_id),
+                            context: check.context.clone(),
+                        })
+                        .collect(),
+                    contexts: self
+                        .required_status_checks
+                        .iter()
+                        .map(|check| check.context.clone())
+                        .collect(),
+                    contexts_url: None,
+                    enforcement_level: None,
+                    strict: Some(self.requires_strict_status_checks),
+                    url: None,
+                }
+            }),
+            restrictions: None,
+            enabled: None,
+            name: None,
+        })
+    }
+}
+
+/// A repository as returned by the `RepositoryFields` fragment
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_excessive_bools)]
+pub struct GraphQLRepository {
+    pub name: String,
+    pub name_with_owner: String,
+    pub owner: GraphQLRepositoryOwner,
+    pub default_branch_ref: Option<Name>,
+    pub is_archived: bool,
+    pub is_disabled: bool,
+    pub is_fork: bool,
+    pub is_template: bool,
+    pub visibility: String,
+    pub rebase_merge_allowed: bool,
+    pub merge_commit_allowed: bool,
+    pub squash_merge_allowed: bool,
+    pub delete_branch_on_merge: bool,
+    pub auto_merge_allowed: bool,
+    pub has_issues_enabled: bool,
+    pub has_projects_enabled: bool,
+    pub has_wiki_enabled: bool,
+    pub primary_language: Option<Name>,
+    pub repository_topics: Nodes<RepositoryTopic>,
+    /// Null if the token isn't allowed to read the branch protection rules
+    pub branch_protection_rules: Option<BranchProtectionRules>,
+}
+
+impl GraphQLRepository {
+    /// Converts the repository to the model the REST API produces.
+    /// Returns None if the repository has to be loaded through the REST API instead, e.g.
+    /// because it is empty and has no default branch
+    pub fn into_repository(self) -> Option<Repository> {
+        let default_branch = self.default_branch_ref?.name;
+
+        let branch_protection_rules = self
+            .branch_protection_rules
+            .filter(|rules| !rules.page_info.has_next_page)
+            .map(|rules| {
+                rules
+                    .nodes
+                    .iter()
+                    .map(|rule| LoadedBranchProtection {
+                        pattern: rule.pattern.clone(),
+                        branch_protection: rule.branch_protection(),
+                    })
+                    .collect()
+            });
+
+        Some(Repository {
+            info: Info {
+                name: self.name,
+                full_name: self.name_with_owner,
+                owner: RepositoryOwner {
+                    login: self.owner.login,
+                    owner_type: self.owner.typename,
+                },
+                default_branch,
+                archived: self.is_archived,
+                disabled: self.is_disabled,
+                visibility: Some(self.visibility.to_lowercase()),
+                allow_rebase_merge: Some(self.rebase_merge_allowed),
+                allow_merge_commit: Some(self.merge_commit_allowed),
+                allow_squash_merge: Some(self.squash_merge_allowed),
+                delete_branch_on_merge: Some(self.delete_branch_on_merge),
+                has_issues: Some(self.has_issues_enabled),
+                has_projects: Some(self.has_projects_enabled),
+                has_downloads: None,
+                has_wiki: Some(self.has_wiki_enabled),
+                allow_auto_merge: Some(self.auto_merge_allowed),
+                language: self.primary_language.map(|language| language.name),
+                fork: self.is_fork,
+                is_template: self.is_template,
+            },
+            topics: Topics {
+                names: self
+                    .repository_topics
+                    .nodes
+                    .into_iter()
+                    .map(|topic| topic.topic.name)
+                    .collect::<HashSet<_>>(),
+            },
+            custom_properties: HashMap::new(),
+            branch_protection_rules,
+        })
+    }
+}
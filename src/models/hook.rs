@@ -0,0 +1,39 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HookConfig {
+    pub url: Option<String>,
+    pub content_type: Option<String>,
+    /// "0" or "1", but some GitHub versions return it as a number
+    pub insecure_ssl: Option<serde_json::Value>,
+}
+
+impl HookConfig {
+    pub fn insecure_ssl(&self) -> bool {
+        match &self.insecure_ssl {
+            Some(serde_json::Value::String(v)) => v != "0",
+            Some(serde_json::Value::Number(v)) => v.as_i64() != Some(0),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hook {
+    pub id: i64,
+    pub name: String,
+    pub active: bool,
+    pub events: Vec<String>,
+    pub config: HookConfig,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HookDelivery {
+    pub status_code: i64,
+}
+
+impl HookDelivery {
+    pub fn failed(&self) -> bool {
+        !(200..300).contains(&self.status_code)
+    }
+}
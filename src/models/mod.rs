@@ -1,11 +1,34 @@
+pub mod branch;
 pub mod branch_protection;
 pub mod branch_protection_update;
+pub mod collaborator;
+pub mod commit;
+pub mod contents;
+pub mod custom_property;
 pub mod de;
+pub mod deploy_key;
+pub mod environment;
+pub mod git;
+pub mod graphql;
+pub mod hook;
+pub mod organization;
+pub mod pull_request;
 pub mod repository;
 pub mod repository_owner;
 
+pub use branch::*;
 pub use branch_protection::*;
 pub use branch_protection_update::*;
+pub use collaborator::*;
+pub use commit::*;
+pub use contents::*;
+pub use custom_property::*;
+pub use deploy_key::*;
+pub use environment::*;
+pub use git::*;
+pub use hook::*;
+pub use organization::*;
+pub use pull_request::*;
 pub use repository::*;
 pub use repository_owner::*;
 
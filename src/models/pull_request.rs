@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct PullRequest {
+    pub number: i64,
+    pub html_url: String,
+}
@@ -1,13 +1,47 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use serde::Deserialize;
 
-use crate::models::RepositoryOwner;
+use crate::models::{BranchProtection, RepositoryOwner};
 
 #[derive(Debug, Deserialize)]
 pub struct Listing {
     pub name: String,      // e.g. lidl-normalize
     pub full_name: String, // e.g. pajlada/lidl-normalize
+    pub owner: RepositoryOwner,
+    #[serde(default)]
+    pub private: bool,
+    // Doesn't exist in older GHE versions, private is used instead
+    pub visibility: Option<String>,
+    #[serde(default)]
+    pub fork: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub topics: Vec<String>,
+}
+
+impl Listing {
+    pub fn visibility(&self) -> &str {
+        match &self.visibility {
+            Some(visibility) => visibility,
+            None if self.private => "private",
+            None => "public",
+        }
+    }
+}
+
+/// A page of repository search results
+#[derive(Debug, Deserialize)]
+pub struct SearchResults {
+    pub incomplete_results: bool,
+    pub items: Vec<Listing>,
+}
+
+/// A page of the repositories a GitHub App installation can access
+#[derive(Debug, Deserialize)]
+pub struct InstallationRepositories {
+    pub repositories: Vec<Listing>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -24,13 +58,22 @@ pub struct Info {
     pub allow_rebase_merge: Option<bool>,
     pub allow_merge_commit: Option<bool>,
     pub allow_squash_merge: Option<bool>,
+    #[allow(dead_code)]
     pub delete_branch_on_merge: Option<bool>,
     pub has_issues: Option<bool>,
     pub has_projects: Option<bool>,
+    #[allow(dead_code)]
     pub has_downloads: Option<bool>,
     pub has_wiki: Option<bool>,
 
     pub allow_auto_merge: Option<bool>, // Doesn't exist in GHE3.0 or GHE3.1, but available in GHE3.2
+
+    /// The primary language of the repository, as detected by GitHub
+    pub language: Option<String>,
+    #[serde(default)]
+    pub fork: bool,
+    #[serde(default)]
+    pub is_template: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,4 +84,79 @@ pub struct Topics {
 pub struct Repository {
     pub info: Info,
     pub topics: Topics,
+    /// Custom property values by property name. Only loaded if the config uses custom properties
+    pub custom_properties: HashMap<String, Vec<String>>,
+    /// Branch protection rules loaded along with the repository through the GraphQL API.
+    /// None if the branch protection has to be loaded per branch
+    pub branch_protection_rules: Option<Vec<LoadedBranchProtection>>,
+}
+
+/// A branch protection rule loaded along with the repository
+#[derive(Debug)]
+pub struct LoadedBranchProtection {
+    pub pattern: String,
+    /// None if the branch protection of matching branches has to be loaded per branch
+    pub branch_protection: Option<BranchProtection>,
+}
+
+impl Repository {
+    /// Returns the loaded branch protection rule applying to a branch, if any.
+    /// Like on GitHub, `*` does not match `/`, and a rule for the exact branch name wins
+    pub fn loaded_branch_protection_rule(
+        &self,
+        branch_name: &str,
+    ) -> Option<&LoadedBranchProtection> {
+        let rules = self.branch_protection_rules.as_ref()?;
+
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..glob::MatchOptions::new()
+        };
+        rules
+            .iter()
+            .find(|rule| rule.pattern == branch_name)
+            .or_else(|| {
+                rules.iter().find(|rule| {
+                    glob::Pattern::new(&rule.pattern)
+                        .is_ok_and(|pattern| pattern.matches_with(branch_name, options))
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+impl Info {
+    /// An active repository of a user with `master` as its default branch.
+    /// `fields` are added to or replace the fields of the repository, e.g. `{"has_wiki": false}`
+    pub fn for_test(full_name: &str, fields: serde_json::Value) -> Self {
+        let (owner, name) = full_name.split_once('/').unwrap();
+        let mut info = serde_json::json!({
+            "name": name,
+            "full_name": full_name,
+            "owner": {"login": owner, "type": "User"},
+            "default_branch": "master",
+            "archived": false,
+            "disabled": false,
+        });
+        if let serde_json::Value::Object(fields) = fields {
+            info.as_object_mut().unwrap().extend(fields);
+        }
+
+        serde_json::from_value(info).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl Repository {
+    /// A repository without topics, custom properties or loaded branch protection rules
+    pub fn for_test(info: Info) -> Self {
+        Repository {
+            info,
+            topics: Topics {
+                names: HashSet::new(),
+            },
+            custom_properties: HashMap::new(),
+            branch_protection_rules: None,
+        }
+    }
 }
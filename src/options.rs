@@ -1,4 +1,7 @@
 pub struct Options {
     pub dry_run: bool,
     pub dry_run_bpr: bool,
+    pub assume_yes: bool,
+    /// Check archived repositories instead of skipping them
+    pub include_archived: bool,
 }
@@ -0,0 +1,318 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::custom_properties::{self, CustomPropertySelector};
+use crate::models::de::glob_pattern;
+use crate::models::{Repository, Team};
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Pull,
+    Triage,
+    Push,
+    Maintain,
+    Admin,
+}
+
+impl Permission {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Permission::Pull => "pull",
+            Permission::Triage => "triage",
+            Permission::Push => "push",
+            Permission::Maintain => "maintain",
+            Permission::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct TeamPermission {
+    /// Slug of a team in the organization owning the repository
+    pub team: String,
+    pub permission: Permission,
+    /// Only apply this permission to repositories with the given topic
+    pub topic: Option<String>,
+    /// Only apply this permission to repositories whose name matches the given glob pattern
+    #[serde(default, deserialize_with = "glob_pattern")]
+    pub repository_pattern: Option<glob::Pattern>,
+    /// Only apply this permission to repositories with the given custom property values
+    pub custom_properties: Option<CustomPropertySelector>,
+}
+
+impl TeamPermission {
+    pub fn applies_to(&self, repository: &Repository) -> bool {
+        if let Some(topic) = &self.topic {
+            if !repository.topics.names.contains(topic) {
+                return false;
+            }
+        }
+
+        if let Some(pattern) = &self.repository_pattern {
+            if !pattern.matches(&repository.info.name) {
+                return false;
+            }
+        }
+
+        custom_properties::matches(
+            self.custom_properties.as_ref(),
+            &repository.custom_properties,
+        )
+    }
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutsideCollaboratorAdmins {
+    /// Outside collaborators with admin rights are not checked
+    #[default]
+    Allow,
+    /// Outside collaborators with admin rights are reported
+    Report,
+    /// Outside collaborators with admin rights are reported, and removed with --fix
+    Remove,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Permissions {
+    #[serde(default)]
+    pub teams: Vec<TeamPermission>,
+    #[serde(default)]
+    pub outside_collaborator_admins: OutsideCollaboratorAdmins,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TeamPermissionChange<'a> {
+    pub team: &'a str,
+    /// The permission the team currently has, or None if it has no access
+    pub actual: Option<String>,
+    pub desired: Permission,
+}
+
+impl Permissions {
+    /// Returns the team permissions that differ from the desired permissions.
+    /// If multiple rules apply to the same team, the last one wins.
+    pub fn diff_teams<'a>(
+        &'a self,
+        repository: &Repository,
+        actual_teams: &[Team],
+    ) -> Vec<TeamPermissionChange<'a>> {
+        let mut changes: Vec<TeamPermissionChange> = Vec::new();
+
+        for rule in self.teams.iter().filter(|rule| rule.applies_to(repository)) {
+            changes.retain(|change| change.team != rule.team);
+
+            let actual = actual_teams
+                .iter()
+                .find(|team| team.slug == rule.team)
+                .map(|team| team.permission.clone());
+
+            if actual.as_deref() != Some(rule.permission.as_str()) {
+                changes.push(TeamPermissionChange {
+                    team: &rule.team,
+                    actual,
+                    desired: rule.permission,
+                });
+            }
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use rstest::rstest;
+
+    use crate::config::load_from_reader;
+    use crate::models::{Info, Topics};
+
+    use super::*;
+
+    fn repository(name: &str, topics: &[&str], properties: &[(&str, &str)]) -> Repository {
+        let info = Info::for_test(
+            &format!("pajlads/{name}"),
+            serde_json::json!({"owner": {"login": "pajlads", "type": "Organization"}}),
+        );
+
+        Repository {
+            topics: Topics {
+                names: topics
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<HashSet<_>>(),
+            },
+            custom_properties: properties
+                .iter()
+                .map(|(name, value)| ((*name).to_string(), vec![(*value).to_string()]))
+                .collect::<HashMap<_, _>>(),
+            ..Repository::for_test(info)
+        }
+    }
+
+    fn team(slug: &str, permission: &str) -> Team {
+        serde_json::from_value(serde_json::json!({
+            "description": null,
+            "html_url": format!("https://github.com/orgs/pajlads/teams/{slug}"),
+            "id": 1,
+            "members_url": "",
+            "name": slug,
+            "node_id": "",
+            "parent": null,
+            "permission": permission,
+            "repositories_url": "",
+            "slug": slug,
+            "url": "",
+        }))
+        .unwrap()
+    }
+
+    fn rule(team: &str, permission: Permission) -> TeamPermission {
+        TeamPermission {
+            team: team.to_string(),
+            permission,
+            topic: None,
+            repository_pattern: None,
+            custom_properties: None,
+        }
+    }
+
+    fn change(
+        team: &str,
+        actual: Option<&str>,
+        desired: Permission,
+    ) -> (String, Option<String>, Permission) {
+        (team.to_string(), actual.map(ToString::to_string), desired)
+    }
+
+    #[rstest]
+    #[case(vec![rule("devs", Permission::Push)], vec![team("devs", "push")], vec![])]
+    #[case(
+        vec![rule("devs", Permission::Push)],
+        vec![team("devs", "pull")],
+        vec![change("devs", Some("pull"), Permission::Push)],
+    )]
+    #[case(
+        vec![rule("devs", Permission::Push)],
+        vec![],
+        vec![change("devs", None, Permission::Push)],
+    )]
+    #[case(
+        vec![TeamPermission { topic: Some("bot".to_string()), ..rule("devs", Permission::Admin) }],
+        vec![team("devs", "push")],
+        vec![change("devs", Some("push"), Permission::Admin)],
+    )]
+    #[case(
+        vec![TeamPermission { topic: Some("web".to_string()), ..rule("devs", Permission::Admin) }],
+        vec![team("devs", "push")],
+        vec![],
+    )]
+    #[case(
+        vec![TeamPermission {
+            repository_pattern: Some(glob::Pattern::new("chatterino*").unwrap()),
+            ..rule("devs", Permission::Admin)
+        }],
+        vec![team("devs", "push")],
+        vec![],
+    )]
+    #[case(
+        vec![TeamPermission {
+            custom_properties: Some(HashMap::from([("tier".to_string(), vec!["1".to_string()])])),
+            ..rule("devs", Permission::Maintain)
+        }],
+        vec![team("devs", "push")],
+        vec![change("devs", Some("push"), Permission::Maintain)],
+    )]
+    #[case(
+        vec![TeamPermission {
+            custom_properties: Some(HashMap::from([("tier".to_string(), vec!["2".to_string()])])),
+            ..rule("devs", Permission::Maintain)
+        }],
+        vec![team("devs", "push")],
+        vec![],
+    )]
+    // The last rule applying to a team wins
+    #[case(
+        vec![rule("devs", Permission::Admin), rule("devs", Permission::Push)],
+        vec![team("devs", "push")],
+        vec![],
+    )]
+    #[case(
+        vec![rule("devs", Permission::Push), rule("devs", Permission::Admin)],
+        vec![team("devs", "push")],
+        vec![change("devs", Some("push"), Permission::Admin)],
+    )]
+    #[case(
+        vec![rule("devs", Permission::Push), rule("ops", Permission::Admin)],
+        vec![team("devs", "pull"), team("ops", "admin")],
+        vec![change("devs", Some("pull"), Permission::Push)],
+    )]
+    fn test_diff_teams(
+        #[case] teams: Vec<TeamPermission>,
+        #[case] actual_teams: Vec<Team>,
+        #[case] expected: Vec<(String, Option<String>, Permission)>,
+    ) {
+        let permissions = Permissions {
+            teams,
+            outside_collaborator_admins: OutsideCollaboratorAdmins::Allow,
+        };
+        let repository = repository("pajbot", &["bot"], &[("tier", "1")]);
+
+        let changes: Vec<_> = permissions
+            .diff_teams(&repository, &actual_teams)
+            .into_iter()
+            .map(|change| (change.team.to_string(), change.actual, change.desired))
+            .collect();
+
+        assert_eq!(changes, expected);
+    }
+
+    #[test]
+    fn test_load_permissions() -> anyhow::Result<()> {
+        let contents = r#"
+{"permissions": {
+    "teams": [
+        {
+            "team": "maintainers",
+            "permission": "maintain",
+            "repository_pattern": "pajbot*"
+        }
+    ],
+    "outside_collaborator_admins": "remove"
+}}"#;
+        let reader = std::io::Cursor::new(contents);
+        let config = load_from_reader(reader)?;
+        let expected = Permissions {
+            teams: vec![TeamPermission {
+                team: "maintainers".to_string(),
+                permission: Permission::Maintain,
+                topic: None,
+                repository_pattern: Some(glob::Pattern::new("pajbot*")?),
+                custom_properties: None,
+            }],
+            outside_collaborator_admins: OutsideCollaboratorAdmins::Remove,
+        };
+
+        assert_eq!(expected, config.permissions.unwrap());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_permissions_invalid_permission() {
+        let contents = r#"{"permissions": {"teams": [{"team": "a", "permission": "write"}]}}"#;
+        let reader = std::io::Cursor::new(contents);
+
+        assert!(load_from_reader(reader).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::{bail, Context, Result};
+
+/// Reads a list of repositories from a file, or from stdin if the path is `-`
+pub fn read(path: &str) -> Result<Vec<String>> {
+    let input = if path == "-" {
+        std::io::read_to_string(std::io::stdin())
+            .context("Failed to read repositories from stdin")?
+    } else {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read repositories from {path}"))?
+    };
+
+    parse(&input).with_context(|| format!("Invalid repository list {path}"))
+}
+
+/// Parses a list of `owner/name` entries.
+///
+/// Entries are either separated by newlines, or given as JSON strings and arrays of strings.
+/// Everything after a `#` on a line is a comment.
+pub fn parse(input: &str) -> Result<Vec<String>> {
+    // Repository names can't contain a #, so it can't be part of an entry
+    let lines: Vec<(usize, &str)> = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.split('#').next().unwrap_or_default().trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+
+    let is_json = lines
+        .first()
+        .is_some_and(|(_, line)| line.starts_with('[') || line.starts_with('"'));
+
+    let repos = if is_json {
+        let json = lines
+            .iter()
+            .map(|(_, line)| *line)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut repos = Vec::new();
+        for value in serde_json::Deserializer::from_str(&json).into_iter::<serde_json::Value>() {
+            match value.context("Invalid JSON")? {
+                serde_json::Value::String(repo) => repos.push(repo),
+                serde_json::Value::Array(values) => {
+                    for value in values {
+                        let serde_json::Value::String(repo) = value else {
+                            bail!("Expected a string, got {value}");
+                        };
+                        repos.push(repo);
+                    }
+                }
+                value => bail!("Expected a string or an array of strings, got {value}"),
+            }
+        }
+
+        for repo in &repos {
+            validate(repo)?;
+        }
+
+        repos
+    } else {
+        lines
+            .into_iter()
+            .map(|(line_number, repo)| {
+                validate(repo).with_context(|| format!("Line {line_number}"))?;
+                Ok(repo.to_string())
+            })
+            .collect::<Result<_>>()?
+    };
+
+    Ok(repos)
+}
+
+/// Ensures the entry is a valid `owner/name` repository name.
+/// Also used for the repositories given with --repo
+pub fn validate(repo: &str) -> Result<()> {
+    let Some((owner, name)) = repo.split_once('/') else {
+        bail!("Invalid repository '{repo}', expected owner/name");
+    };
+
+    if owner.is_empty() || !owner.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        bail!("Invalid owner in repository '{repo}'");
+    }
+
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        bail!("Invalid name in repository '{repo}'");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("pajlada/pajbot\npajlads/chatterino\n", &["pajlada/pajbot", "pajlads/chatterino"])]
+    #[case(
+        "# generated from the service catalog\n\npajlada/pajbot # the bot\n  pajlads/chatterino\n",
+        &["pajlada/pajbot", "pajlads/chatterino"],
+    )]
+    #[case(
+        "# generated\n[\n  \"pajlada/pajbot\", # the bot\n  \"pajlads/chatterino\"\n]\n",
+        &["pajlada/pajbot", "pajlads/chatterino"],
+    )]
+    #[case("\"pajlada/pajbot\"\n\"pajlads/chatterino\"\n", &["pajlada/pajbot", "pajlads/chatterino"])]
+    #[case("# nothing here\n", &[])]
+    fn test_parse(#[case] input: &str, #[case] expected: &[&str]) {
+        assert_eq!(parse(input).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("pajlada/pajbot\npajbot\n", "Line 2")]
+    #[case("pajlada/pajbot/extra\n", "Invalid name")]
+    #[case("pajl ada/pajbot\n", "Invalid owner")]
+    #[case("pajlada/..\n", "Invalid name")]
+    #[case("[\"pajlada/pajbot\", 5]\n", "Expected a string")]
+    #[case("[\"pajlada/pajbot\"\n", "Invalid JSON")]
+    fn test_parse_invalid(#[case] input: &str, #[case] expected_error: &str) {
+        let error = format!("{:#}", parse(input).unwrap_err());
+        assert!(error.contains(expected_error), "{error}");
+    }
+}
@@ -1,24 +1,32 @@
+use anyhow::Context;
 use serde::Deserialize;
 use serde_json::json;
 use std::collections::HashMap;
 use std::io::Read;
 
+use crate::constraint::Constraint;
 use crate::models::repository;
 
-macro_rules! ensure_same {
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeMethod {
+    Merge,
+    Squash,
+    Rebase,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct MergeMethods {
+    pub at_least_one_of: Vec<MergeMethod>,
+}
+
+/// Returns the value the setting must be changed to, if the actual value violates the constraint
+macro_rules! ensure_satisfied {
     ($s:ident, $r:ident, $field_name:ident) => {
-        if let Some(expected) = &$s.$field_name {
-            if let Some(actual) = &$r.$field_name {
-                if expected != actual {
-                    Some(expected.clone())
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
+        match (&$s.$field_name, &$r.$field_name) {
+            (Some(constraint), Some(actual)) => constraint.violation(actual).map(Constraint::Exact),
+            _ => None,
         }
     };
 }
@@ -30,6 +38,8 @@ macro_rules! define_repository_settings {
             $(
                 pub $field_name: $field_type,
             )*
+            /// At least one of these merge methods must be allowed
+            pub merge_methods: Option<MergeMethods>,
         }
 
         impl RepositorySettings {
@@ -39,6 +49,7 @@ macro_rules! define_repository_settings {
             //     )*
             // }
 
+            /// Meant to be used on the result of diff, which only contains exact values
             pub fn dump_patch(&self) -> HashMap<&str, serde_json::Value> {
                 let mut map = HashMap::new();
 
@@ -51,7 +62,23 @@ macro_rules! define_repository_settings {
                     return map;
             }
 
+            fn validate_constraints(&self) -> Result<(), anyhow::Error> {
+                $(
+                    if let Some(constraint) = &self.$field_name {
+                        constraint
+                            .validate()
+                            .with_context(|| format!("Invalid {}", stringify!($field_name)))?;
+                    }
+                )*
+
+                Ok(())
+            }
+
             pub fn empty(&self) -> bool {
+                if self.merge_methods.is_some() {
+                    return false;
+                }
+
                 $(
                     if self.$field_name.is_some() {
                         return false;
@@ -62,11 +89,16 @@ macro_rules! define_repository_settings {
             }
 
             pub fn diff(&self, repository: &repository::Info) -> RepositorySettings {
-                RepositorySettings {
+                let mut diff = RepositorySettings {
                 $(
-                    $field_name: ensure_same!(self, repository, $field_name),
+                    $field_name: ensure_satisfied!(self, repository, $field_name),
                 )*
-                }
+                    merge_methods: None,
+                };
+
+                diff.ensure_merge_method(self, repository);
+
+                diff
             }
 
             // diff is used instead
@@ -89,6 +121,86 @@ macro_rules! define_repository_settings {
 }
 
 impl RepositorySettings {
+    fn merge_method(&self, merge_method: MergeMethod) -> Option<&Constraint<bool>> {
+        match merge_method {
+            MergeMethod::Merge => self.allow_merge_commit.as_ref(),
+            MergeMethod::Squash => self.allow_squash_merge.as_ref(),
+            MergeMethod::Rebase => self.allow_rebase_merge.as_ref(),
+        }
+    }
+
+    /// Whether the constraint on the merge method doesn't allow it to be enabled
+    fn disallows(&self, merge_method: MergeMethod) -> bool {
+        self.merge_method(merge_method)
+            .is_some_and(|constraint| !constraint.is_satisfied_by(&true))
+    }
+
+    /// If none of the merge methods will be allowed after applying the diff, allow the first one
+    /// that isn't required to be disallowed
+    fn ensure_merge_method(&mut self, desired: &RepositorySettings, repository: &repository::Info) {
+        let Some(merge_methods) = &desired.merge_methods else {
+            return;
+        };
+
+        let allowed = |merge_method: MergeMethod| {
+            let actual = match merge_method {
+                MergeMethod::Merge => repository.allow_merge_commit,
+                MergeMethod::Squash => repository.allow_squash_merge,
+                MergeMethod::Rebase => repository.allow_rebase_merge,
+            };
+            match self.merge_method(merge_method) {
+                Some(Constraint::Exact(fixed)) => *fixed,
+                _ => actual.unwrap_or(false),
+            }
+        };
+
+        if merge_methods.at_least_one_of.iter().any(|m| allowed(*m)) {
+            return;
+        }
+
+        let first = merge_methods
+            .at_least_one_of
+            .iter()
+            .find(|m| !desired.disallows(**m));
+        if let Some(first) = first {
+            let fix = Some(Constraint::Exact(true));
+            match first {
+                MergeMethod::Merge => self.allow_merge_commit = fix,
+                MergeMethod::Squash => self.allow_squash_merge = fix,
+                MergeMethod::Rebase => self.allow_rebase_merge = fix,
+            }
+        }
+    }
+
+    /// Rejects invalid constraints. GitHub requires at least one merge method to be allowed
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        self.validate_constraints()?;
+
+        if self.disallows(MergeMethod::Merge)
+            && self.disallows(MergeMethod::Squash)
+            && self.disallows(MergeMethod::Rebase)
+        {
+            anyhow::bail!(
+                "allow_merge_commit, allow_squash_merge and allow_rebase_merge can't all be disallowed"
+            );
+        }
+
+        if let Some(merge_methods) = &self.merge_methods {
+            if merge_methods.at_least_one_of.is_empty() {
+                anyhow::bail!("merge_methods.at_least_one_of can't be empty");
+            }
+            if merge_methods
+                .at_least_one_of
+                .iter()
+                .all(|m| self.disallows(*m))
+            {
+                anyhow::bail!("every merge method in merge_methods.at_least_one_of is disallowed");
+            }
+        }
+
+        Ok(())
+    }
+
     #[allow(dead_code)]
     fn load<R>(rdr: R) -> Result<RepositorySettings, anyhow::Error>
     where
@@ -101,14 +213,14 @@ impl RepositorySettings {
 }
 
 define_repository_settings! {
-    visibility : Option<String>,
-    allow_auto_merge : Option<bool>,
-    has_issues : Option<bool>,
-    has_projects : Option<bool>,
-    has_wiki : Option<bool>,
-    allow_merge_commit : Option<bool>,
-    allow_squash_merge : Option<bool>,
-    allow_rebase_merge : Option<bool>,
+    visibility : Option<Constraint<String>>,
+    allow_auto_merge : Option<Constraint<bool>>,
+    has_issues : Option<Constraint<bool>>,
+    has_projects : Option<Constraint<bool>>,
+    has_wiki : Option<Constraint<bool>>,
+    allow_merge_commit : Option<Constraint<bool>>,
+    allow_squash_merge : Option<Constraint<bool>>,
+    allow_rebase_merge : Option<Constraint<bool>>,
 }
 
 #[cfg(test)]
@@ -133,29 +245,144 @@ mod tests {
             settings,
             RepositorySettings {
                 visibility: None,
-                allow_auto_merge: Some(false),
+                allow_auto_merge: Some(Constraint::Exact(false)),
                 has_issues: None,
-                has_projects: Some(false),
+                has_projects: Some(Constraint::Exact(false)),
                 has_wiki: None,
-                allow_merge_commit: Some(false),
-                allow_squash_merge: Some(true),
-                allow_rebase_merge: Some(false),
+                allow_merge_commit: Some(Constraint::Exact(false)),
+                allow_squash_merge: Some(Constraint::Exact(true)),
+                allow_rebase_merge: Some(Constraint::Exact(false)),
+                merge_methods: None,
             }
         );
         assert_eq!(
             settings,
             RepositorySettings {
                 visibility: None,
-                allow_auto_merge: Some(true),
+                allow_auto_merge: Some(Constraint::Exact(true)),
                 has_issues: None,
-                has_projects: Some(false),
+                has_projects: Some(Constraint::Exact(false)),
                 has_wiki: None,
-                allow_merge_commit: Some(false),
-                allow_squash_merge: Some(true),
-                allow_rebase_merge: Some(false),
+                allow_merge_commit: Some(Constraint::Exact(false)),
+                allow_squash_merge: Some(Constraint::Exact(true)),
+                allow_rebase_merge: Some(Constraint::Exact(false)),
+                merge_methods: None,
             }
         );
 
         Ok(())
     }
+
+    #[test]
+    fn test_diff_constraints() -> anyhow::Result<()> {
+        let contents = r#"
+{
+  "visibility": {"one_of": ["private", "internal"]},
+  "has_wiki": false,
+  "merge_methods": {"at_least_one_of": ["rebase", "squash"]}
+}"#;
+        let settings = RepositorySettings::load(std::io::Cursor::new(contents))?;
+        let repository = |visibility: &str, allow_squash_merge: bool| {
+            repository::Info::for_test(
+                "pajlada/a",
+                serde_json::json!({
+                    "visibility": visibility,
+                    "has_wiki": false,
+                    "allow_merge_commit": true,
+                    "allow_squash_merge": allow_squash_merge,
+                    "allow_rebase_merge": false,
+                }),
+            )
+        };
+
+        assert!(settings.diff(&repository("internal", true)).empty());
+
+        let diff = settings.diff(&repository("public", false));
+        assert_eq!(
+            diff.dump_patch(),
+            HashMap::from([
+                ("visibility", json!("private")),
+                ("allow_rebase_merge", json!(true)),
+            ])
+        );
+
+        // A merge method that must be disallowed is never the one that gets allowed
+        let contents = r#"
+{
+  "allow_rebase_merge": false,
+  "merge_methods": {"at_least_one_of": ["rebase", "squash"]}
+}"#;
+        let settings = RepositorySettings::load(std::io::Cursor::new(contents))?;
+        let diff = settings.diff(&repository("private", false));
+        assert_eq!(
+            diff.dump_patch(),
+            HashMap::from([("allow_squash_merge", json!(true))])
+        );
+
+        // The same goes for a merge method that must be one of only disallowed values
+        let contents = r#"
+{
+  "allow_rebase_merge": {"one_of": [false]},
+  "merge_methods": {"at_least_one_of": ["rebase", "squash"]}
+}"#;
+        let settings = RepositorySettings::load(std::io::Cursor::new(contents))?;
+        let diff = settings.diff(&repository("private", false));
+        assert_eq!(
+            diff.dump_patch(),
+            HashMap::from([("allow_squash_merge", json!(true))])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_merge_method_allowed() {
+        let contents = r#"
+{"settings": {
+  "allow_merge_commit": false,
+  "allow_squash_merge": false,
+  "allow_rebase_merge": false
+}}"#;
+        let reader = std::io::Cursor::new(contents);
+
+        assert!(crate::config::load_from_reader(reader).is_err());
+    }
+
+    #[test]
+    fn test_no_merge_method_allowed_by_one_of() {
+        for settings in [
+            r#"{
+                "allow_merge_commit": {"one_of": [false]},
+                "allow_squash_merge": {"one_of": [false]},
+                "allow_rebase_merge": {"one_of": [false]}
+            }"#,
+            r#"{
+                "allow_rebase_merge": {"one_of": [false]},
+                "allow_squash_merge": false,
+                "merge_methods": {"at_least_one_of": ["rebase", "squash"]}
+            }"#,
+        ] {
+            let contents = format!(r#"{{"settings": {settings}}}"#);
+            let reader = std::io::Cursor::new(contents);
+
+            assert!(crate::config::load_from_reader(reader).is_err());
+        }
+    }
+
+    #[test]
+    fn test_invalid_constraints() {
+        for contents in [
+            r#"{"settings": {"visibility": {"one_of": []}}}"#,
+            r#"{"settings": {"has_wiki": {}}}"#,
+            r#"{"branch_protections": [{
+                "branch_name_pattern": "master",
+                "operation": "must_exist",
+                "required_approving_review_count": {"min": 3, "max": 1}
+            }]}"#,
+        ] {
+            let reader = std::io::Cursor::new(contents);
+
+            assert!(crate::config::load_from_reader(reader).is_err());
+        }
+    }
 }
@@ -0,0 +1,218 @@
+use std::fmt;
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+
+use crate::models::{
+    CheckRun, CommitStatus, ProtectedBranchRequiredStatusCheck,
+    ProtectedBranchRequiredStatusCheckChecksItem,
+};
+
+fn default_recent_commits() -> usize {
+    10
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct StatusChecks {
+    /// Required status checks must have reported on at least one of this many commits on the
+    /// default branch
+    #[serde(default = "default_recent_commits")]
+    pub recent_commits: usize,
+}
+
+impl StatusChecks {
+    /// The recent commits are loaded with a single request, which returns at most 100 commits
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=100).contains(&self.recent_commits) {
+            bail!("recent_commits must be between 1 and 100");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StatusCheckProblem {
+    NotReported {
+        context: String,
+    },
+    /// The check was reported, but not by the app the branch protection expects
+    WrongApp {
+        context: String,
+        expected_app_id: i64,
+        actual_app_ids: Vec<i64>,
+    },
+}
+
+impl fmt::Display for StatusCheckProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusCheckProblem::NotReported { context } => {
+                write!(f, "{context} has not reported")
+            }
+            StatusCheckProblem::WrongApp {
+                context,
+                expected_app_id,
+                actual_app_ids,
+            } => write!(
+                f,
+                "{context} was reported by app {actual_app_ids:?}, but app {expected_app_id} is required"
+            ),
+        }
+    }
+}
+
+/// The status checks required by a branch protection.
+/// Older branch protections only list the contexts, without the app that must report them
+pub fn required_checks(
+    required_status_checks: &ProtectedBranchRequiredStatusCheck,
+) -> Vec<ProtectedBranchRequiredStatusCheckChecksItem> {
+    if required_status_checks.checks.is_empty() {
+        required_status_checks
+            .contexts
+            .iter()
+            .map(|context| ProtectedBranchRequiredStatusCheckChecksItem {
+                app_id: None,
+                context: context.clone(),
+            })
+            .collect()
+    } else {
+        required_status_checks.checks.clone()
+    }
+}
+
+/// Compares the required checks against the statuses and check runs reported on recent commits
+pub fn validate(
+    required_checks: &[ProtectedBranchRequiredStatusCheckChecksItem],
+    statuses: &[CommitStatus],
+    check_runs: &[CheckRun],
+) -> Vec<StatusCheckProblem> {
+    let mut problems = Vec::new();
+
+    for required_check in required_checks {
+        let context = &required_check.context;
+
+        let mut actual_app_ids: Vec<i64> = check_runs
+            .iter()
+            .filter(|check_run| &check_run.name == context)
+            .filter_map(|check_run| check_run.app.as_ref().map(|app| app.id))
+            .collect();
+        actual_app_ids.sort_unstable();
+        actual_app_ids.dedup();
+
+        let has_check_run = check_runs
+            .iter()
+            .any(|check_run| &check_run.name == context);
+        // Commit statuses don't say which app created them, so they can't be verified
+        let has_status = statuses.iter().any(|status| &status.context == context);
+
+        match required_check.app_id {
+            Some(expected_app_id)
+                if has_check_run && !has_status && !actual_app_ids.contains(&expected_app_id) =>
+            {
+                problems.push(StatusCheckProblem::WrongApp {
+                    context: context.clone(),
+                    expected_app_id,
+                    actual_app_ids,
+                });
+            }
+            _ if !has_check_run && !has_status => {
+                problems.push(StatusCheckProblem::NotReported {
+                    context: context.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::CheckRunApp;
+
+    use super::*;
+
+    fn required(
+        context: &str,
+        app_id: Option<i64>,
+    ) -> ProtectedBranchRequiredStatusCheckChecksItem {
+        ProtectedBranchRequiredStatusCheckChecksItem {
+            app_id,
+            context: context.to_string(),
+        }
+    }
+
+    fn check_run(name: &str, app_id: i64) -> CheckRun {
+        CheckRun {
+            name: name.to_string(),
+            app: Some(CheckRunApp { id: app_id }),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let required_checks = vec![
+            required("build (ubuntu)", Some(15368)),
+            required("build (windows)", Some(15368)),
+            required("lint", None),
+            required("ci/jenkins", None),
+            required("biuld (macos)", None),
+        ];
+        let statuses = vec![CommitStatus {
+            context: "ci/jenkins".to_string(),
+        }];
+        let check_runs = vec![
+            check_run("build (ubuntu)", 15368),
+            check_run("build (windows)", 1),
+            check_run("lint", 1),
+            check_run("build (macos)", 15368),
+        ];
+
+        assert_eq!(
+            validate(&required_checks, &statuses, &check_runs),
+            vec![
+                StatusCheckProblem::WrongApp {
+                    context: "build (windows)".to_string(),
+                    expected_app_id: 15368,
+                    actual_app_ids: vec![1],
+                },
+                StatusCheckProblem::NotReported {
+                    context: "biuld (macos)".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recent_commits_out_of_range() {
+        for recent_commits in [0, 101] {
+            let contents =
+                format!(r#"{{"status_checks": {{"recent_commits": {recent_commits}}}}}"#);
+            let reader = std::io::Cursor::new(contents);
+
+            assert!(crate::config::load_from_reader(reader).is_err());
+        }
+    }
+
+    #[test]
+    fn test_required_checks_from_contexts() {
+        let required_status_checks = ProtectedBranchRequiredStatusCheck {
+            checks: vec![],
+            contexts: vec!["lint".to_string()],
+            contexts_url: None,
+            enforcement_level: None,
+            strict: None,
+            url: None,
+        };
+
+        assert_eq!(
+            required_checks(&required_status_checks)
+                .iter()
+                .map(|check| (check.context.as_str(), check.app_id))
+                .collect::<Vec<_>>(),
+            vec![("lint", None)]
+        );
+    }
+}
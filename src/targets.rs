@@ -0,0 +1,185 @@
+use crate::models::repository::Listing;
+
+/// Filters for the repositories of the authenticated user
+#[derive(Debug, Default)]
+pub struct AuthenticatedUserFilter {
+    /// Any of `owner`, `collaborator` and `organization_member`. Empty means all of them
+    pub affiliation: Vec<String>,
+    /// `all`, `public` or `private`
+    pub visibility: Option<String>,
+}
+
+/// Filters for the listed repositories, applied before the repositories are loaded
+#[derive(Debug, Default)]
+pub struct RepositoryFilter {
+    /// Only repositories matching one of these patterns. Patterns containing a `/` are matched
+    /// against the full name (`owner/name`), other patterns against the name
+    pub include: Vec<glob::Pattern>,
+    /// Skip repositories matching one of these patterns
+    pub exclude: Vec<glob::Pattern>,
+    /// Only repositories with all of these topics
+    pub topics: Vec<String>,
+    /// `public`, `private` or `internal`
+    pub visibility: Option<String>,
+    pub skip_forks: bool,
+    pub include_archived: bool,
+    /// `user` or `organization`
+    pub owner_type: Option<String>,
+}
+
+fn matches_pattern(pattern: &glob::Pattern, listing: &Listing) -> bool {
+    if pattern.as_str().contains('/') {
+        pattern.matches(&listing.full_name)
+    } else {
+        pattern.matches(&listing.name)
+    }
+}
+
+impl RepositoryFilter {
+    pub fn matches(&self, listing: &Listing) -> bool {
+        if !self.include.is_empty()
+            && !self
+                .include
+                .iter()
+                .any(|pattern| matches_pattern(pattern, listing))
+        {
+            return false;
+        }
+
+        if self
+            .exclude
+            .iter()
+            .any(|pattern| matches_pattern(pattern, listing))
+        {
+            return false;
+        }
+
+        if !self
+            .topics
+            .iter()
+            .all(|topic| listing.topics.contains(topic))
+        {
+            return false;
+        }
+
+        if let Some(visibility) = &self.visibility {
+            if visibility != listing.visibility() {
+                return false;
+            }
+        }
+
+        if let Some(owner_type) = &self.owner_type {
+            if !owner_type.eq_ignore_ascii_case(&listing.owner.owner_type) {
+                return false;
+            }
+        }
+
+        !(self.skip_forks && listing.fork) && (self.include_archived || !listing.archived)
+    }
+}
+
+/// The repositories to check, as given on the command line
+#[derive(Debug, Default)]
+pub struct Targets {
+    pub repos: Vec<String>,
+    /// Repositories read from --repos-from
+    pub listed_repos: Vec<String>,
+    pub users: Vec<String>,
+    pub organizations: Vec<String>,
+    /// Check the repositories of every organization of the GitHub instance
+    pub all_organizations: bool,
+    /// Organization and slug of the teams whose repositories to check
+    pub teams: Vec<(String, String)>,
+    /// GitHub search queries
+    pub searches: Vec<String>,
+    /// Check the repositories of the GitHub App installation the token belongs to
+    pub installation: bool,
+    /// Check the repositories of the authenticated user
+    pub me: Option<AuthenticatedUserFilter>,
+    /// Filters for the listed repositories.
+    /// Repositories given with --repo or --repos-from are always checked
+    pub filter: RepositoryFilter,
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn listing(full_name: &str, owner_type: &str, fork: bool, topics: &[&str]) -> Listing {
+        serde_json::from_value(serde_json::json!({
+            "name": full_name.split_once('/').unwrap().1,
+            "full_name": full_name,
+            "owner": {"login": full_name.split_once('/').unwrap().0, "type": owner_type},
+            "private": false,
+            "fork": fork,
+            "archived": full_name.ends_with("-archived"),
+            "topics": topics,
+        }))
+        .unwrap()
+    }
+
+    fn patterns(patterns: &[&str]) -> Vec<glob::Pattern> {
+        patterns
+            .iter()
+            .map(|pattern| glob::Pattern::new(pattern).unwrap())
+            .collect()
+    }
+
+    #[rstest]
+    #[case(RepositoryFilter::default(), listing("pajlada/pajbot", "User", false, &[]), true)]
+    #[case(RepositoryFilter::default(), listing("pajlada/pajbot-archived", "User", false, &[]), false)]
+    #[case(
+        RepositoryFilter { include_archived: true, ..Default::default() },
+        listing("pajlada/pajbot-archived", "User", false, &[]),
+        true,
+    )]
+    #[case(
+        RepositoryFilter { include: patterns(&["pajbot*"]), ..Default::default() },
+        listing("pajlada/chatterino", "User", false, &[]),
+        false,
+    )]
+    #[case(
+        RepositoryFilter { include: patterns(&["pajlads/*"]), ..Default::default() },
+        listing("pajlads/pajbot", "Organization", false, &[]),
+        true,
+    )]
+    #[case(
+        RepositoryFilter { exclude: patterns(&["*-test"]), ..Default::default() },
+        listing("pajlada/pajbot-test", "User", false, &[]),
+        false,
+    )]
+    #[case(
+        RepositoryFilter { topics: vec!["bot".to_string()], ..Default::default() },
+        listing("pajlada/pajbot", "User", false, &["bot", "twitch"]),
+        true,
+    )]
+    #[case(
+        RepositoryFilter { topics: vec!["bot".to_string()], ..Default::default() },
+        listing("pajlada/chatterino", "User", false, &["twitch"]),
+        false,
+    )]
+    #[case(
+        RepositoryFilter { visibility: Some("private".to_string()), ..Default::default() },
+        listing("pajlada/pajbot", "User", false, &[]),
+        false,
+    )]
+    #[case(
+        RepositoryFilter { skip_forks: true, ..Default::default() },
+        listing("pajlada/pajbot", "User", true, &[]),
+        false,
+    )]
+    #[case(
+        RepositoryFilter { owner_type: Some("organization".to_string()), ..Default::default() },
+        listing("pajlada/pajbot", "User", false, &[]),
+        false,
+    )]
+    fn test_repository_filter(
+        #[case] filter: RepositoryFilter,
+        #[case] listing: Listing,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(filter.matches(&listing), expected);
+    }
+}
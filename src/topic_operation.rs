@@ -1,11 +1,404 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
 use serde::Deserialize;
 
+use crate::models::de::{regex, required_glob_pattern};
+
 pub type TopicOperations = Vec<TopicOperation>;
 
+/// GitHub refuses to set more topics than this on a repository
+pub const MAX_TOPICS: usize = 20;
+const MAX_TOPIC_LENGTH: usize = 50;
+
+/// Checks that GitHub accepts the topic: lowercase letters, numbers and hyphens, starting with a
+/// letter or number, and at most 50 characters long
+pub fn validate_topic(topic: &str) -> Result<()> {
+    let valid_characters = topic
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    let valid_start = topic
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+
+    if !valid_characters || !valid_start {
+        bail!("invalid topic '{topic}': topics must start with a lowercase letter or number, and can only contain lowercase letters, numbers and hyphens");
+    }
+    if topic.len() > MAX_TOPIC_LENGTH {
+        bail!("invalid topic '{topic}': topics can't be longer than {MAX_TOPIC_LENGTH} characters");
+    }
+
+    Ok(())
+}
+
+/// Checks that GitHub accepts the topics of a repository
+pub fn validate_topics(topics: &HashSet<String>) -> Result<()> {
+    for topic in topics {
+        validate_topic(topic)?;
+    }
+    if topics.len() > MAX_TOPICS {
+        bail!(
+            "a repository can't have more than {MAX_TOPICS} topics, but it would have {}",
+            topics.len()
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct TopicVocabulary {
+    /// The only topics repositories may have
+    pub allowed: HashSet<String>,
+    /// Remove topics that are not allowed with --fix, instead of only reporting them
+    #[serde(default)]
+    pub remove_unknown: bool,
+}
+
+impl TopicVocabulary {
+    pub fn validate(&self) -> Result<()> {
+        for topic in &self.allowed {
+            validate_topic(topic)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the topics that are not part of the vocabulary, sorted by name
+    pub fn unknown<'a>(&self, topics: &'a HashSet<String>) -> Vec<&'a String> {
+        let mut unknown: Vec<&String> = topics
+            .iter()
+            .filter(|topic| !self.allowed.contains(*topic))
+            .collect();
+        unknown.sort();
+        unknown
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopicPattern {
+    #[serde(deserialize_with = "regex")]
+    Regex(::regex::Regex),
+    #[serde(deserialize_with = "required_glob_pattern")]
+    Glob(glob::Pattern),
+}
+
+impl TopicPattern {
+    pub fn is_match(&self, topic: &str) -> bool {
+        match self {
+            TopicPattern::Regex(regex) => regex.is_match(topic),
+            TopicPattern::Glob(pattern) => pattern.matches(topic),
+        }
+    }
+
+    /// Returns the new name of a matching topic.
+    /// For regex patterns, the matched part is replaced and `$1` etc. refer to capture groups
+    fn rename(&self, topic: &str, name: &str) -> String {
+        match self {
+            TopicPattern::Regex(regex) => regex.replace(topic, name).into_owned(),
+            TopicPattern::Glob(_) => name.to_string(),
+        }
+    }
+}
+
+impl PartialEq for TopicPattern {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TopicPattern::Regex(a), TopicPattern::Regex(b)) => a.as_str() == b.as_str(),
+            (TopicPattern::Glob(a), TopicPattern::Glob(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for TopicPattern {}
+
+/// A fact about a repository a topic can be derived from
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TopicFact {
+    /// The primary language of the repository is this language (case insensitive)
+    Language(String),
+    /// The repository has this file on its default branch
+    File(String),
+    Fork,
+    Template,
+}
+
+/// The facts about a repository topics are derived from
+#[derive(Debug, Default)]
+pub struct RepositoryFacts {
+    pub language: Option<String>,
+    pub fork: bool,
+    pub template: bool,
+    /// The files probed for by `file` facts that exist
+    pub existing_files: HashSet<String>,
+}
+
+impl TopicFact {
+    fn holds(&self, facts: &RepositoryFacts) -> bool {
+        match self {
+            TopicFact::Language(language) => facts
+                .language
+                .as_ref()
+                .is_some_and(|actual| actual.eq_ignore_ascii_case(language)),
+            TopicFact::File(path) => facts.existing_files.contains(path),
+            TopicFact::Fork => facts.fork,
+            TopicFact::Template => facts.template,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, PartialEq, Eq)]
 #[serde(tag = "operation", rename_all = "snake_case")]
 pub enum TopicOperation {
-    MustExist { name: String },
-    MustNotExist { name: String },
-    Rename { old_name: String, name: String },
+    MustExist {
+        name: String,
+        /// Only add the topic if the repository has this topic
+        if_exists: Option<String>,
+    },
+    MustNotExist {
+        name: String,
+    },
+    Rename {
+        old_name: String,
+        name: String,
+    },
+    /// Remove every topic matching the `regex` or `glob` pattern
+    RemoveMatching {
+        #[serde(flatten)]
+        pattern: TopicPattern,
+    },
+    /// Rename every topic matching the `regex` or `glob` pattern
+    RenameMatching {
+        #[serde(flatten)]
+        pattern: TopicPattern,
+        name: String,
+    },
+    /// Add the topic if the fact holds for the repository
+    Derive {
+        from: TopicFact,
+        name: String,
+    },
+}
+
+impl TopicOperation {
+    /// Checks that the topics the operation adds are accepted by GitHub and part of the vocabulary
+    pub fn validate(&self, vocabulary: Option<&TopicVocabulary>) -> Result<()> {
+        let name = match self {
+            TopicOperation::MustExist { name, .. }
+            | TopicOperation::Rename { name, .. }
+            | TopicOperation::Derive { name, .. }
+            | TopicOperation::RenameMatching {
+                pattern: TopicPattern::Glob(_),
+                name,
+            } => name,
+            // Regex replacements can only be checked once the topics are known
+            TopicOperation::MustNotExist { .. }
+            | TopicOperation::RemoveMatching { .. }
+            | TopicOperation::RenameMatching { .. } => return Ok(()),
+        };
+
+        validate_topic(name)?;
+        if let Some(vocabulary) = vocabulary {
+            if !vocabulary.allowed.contains(name) {
+                bail!("topic '{name}' is not part of the topic vocabulary");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the files `derive` operations need to know the existence of
+pub fn probed_files(operations: &[TopicOperation]) -> Vec<&str> {
+    operations
+        .iter()
+        .filter_map(|operation| match operation {
+            TopicOperation::Derive {
+                from: TopicFact::File(path),
+                ..
+            } => Some(path.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Applies the operations in order, returning the topics the repository should have
+pub fn apply(
+    operations: &[TopicOperation],
+    topics: &HashSet<String>,
+    facts: &RepositoryFacts,
+) -> HashSet<String> {
+    let mut final_topics = topics.clone();
+
+    for operation in operations {
+        match operation {
+            TopicOperation::MustExist { name, if_exists } => {
+                if if_exists
+                    .as_ref()
+                    .is_none_or(|topic| final_topics.contains(topic))
+                {
+                    final_topics.insert(name.clone());
+                }
+            }
+            TopicOperation::MustNotExist { name } => {
+                final_topics.remove(name);
+            }
+            TopicOperation::Rename { old_name, name } => {
+                if final_topics.remove(old_name) {
+                    final_topics.insert(name.clone());
+                }
+            }
+            TopicOperation::RemoveMatching { pattern } => {
+                final_topics.retain(|topic| !pattern.is_match(topic));
+            }
+            TopicOperation::RenameMatching { pattern, name } => {
+                let (matching, mut rest): (HashSet<String>, HashSet<String>) = final_topics
+                    .into_iter()
+                    .partition(|topic| pattern.is_match(topic));
+                rest.extend(matching.iter().map(|topic| pattern.rename(topic, name)));
+                final_topics = rest;
+            }
+            TopicOperation::Derive { from, name } => {
+                if from.holds(facts) {
+                    final_topics.insert(name.clone());
+                }
+            }
+        }
+    }
+
+    final_topics
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::load_from_reader;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn topics(names: &[&str]) -> HashSet<String> {
+        names.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_apply_topic_operations() -> anyhow::Result<()> {
+        let contents = r#"
+{"topics": [
+    {"operation": "remove_matching", "regex": "^hacktoberfest-\\d+$"},
+    {"operation": "rename_matching", "regex": "^pajbot(\\d+)$", "name": "pajbot-$1"},
+    {"operation": "rename_matching", "glob": "chatterino-*", "name": "chatterino"},
+    {"operation": "must_exist", "name": "bot", "if_exists": "pajbot-2021"},
+    {"operation": "must_exist", "name": "twitch", "if_exists": "pajbot2021"}
+]}"#;
+        let config = load_from_reader(std::io::Cursor::new(contents))?;
+        let operations = config.topics.unwrap();
+
+        assert_eq!(
+            apply(
+                &operations,
+                &topics(&[
+                    "hacktoberfest",
+                    "hacktoberfest-2021",
+                    "pajbot2021",
+                    "chatterino-plugin",
+                    "chatterino-theme",
+                ]),
+                &RepositoryFacts::default()
+            ),
+            topics(&["hacktoberfest", "pajbot-2021", "chatterino", "bot"])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_topics() -> anyhow::Result<()> {
+        let contents = r#"
+{"topics": [
+    {"operation": "derive", "from": {"language": "rust"}, "name": "rust"},
+    {"operation": "derive", "from": {"file": "package.json"}, "name": "javascript"},
+    {"operation": "derive", "from": {"file": "Cargo.toml"}, "name": "cargo"},
+    {"operation": "derive", "from": "fork", "name": "fork"},
+    {"operation": "derive", "from": "template", "name": "template"}
+]}"#;
+        let config = load_from_reader(std::io::Cursor::new(contents))?;
+        let operations = config.topics.unwrap();
+        let facts = RepositoryFacts {
+            language: Some("Rust".to_string()),
+            fork: false,
+            template: true,
+            existing_files: topics(&["Cargo.toml"]),
+        };
+
+        assert_eq!(
+            probed_files(&operations),
+            vec!["package.json", "Cargo.toml"]
+        );
+        assert_eq!(
+            apply(&operations, &topics(&["bot"]), &facts),
+            topics(&["bot", "rust", "cargo", "template"])
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("pajbot", true)]
+    #[case("2021-hacktoberfest", true)]
+    #[case("PajBot", false)]
+    #[case("-pajbot", false)]
+    #[case("pajbot_2", false)]
+    #[case("", false)]
+    #[case(&"a".repeat(50), true)]
+    #[case(&"a".repeat(51), false)]
+    fn test_validate_topic(#[case] topic: &str, #[case] valid: bool) {
+        assert_eq!(validate_topic(topic).is_ok(), valid);
+    }
+
+    #[test]
+    fn test_too_many_topics() {
+        let names: Vec<String> = (0..=MAX_TOPICS).map(|i| format!("topic-{i}")).collect();
+        let mut topics: HashSet<String> = names.into_iter().collect();
+
+        assert!(validate_topics(&topics).is_err());
+        topics.remove("topic-0");
+        assert!(validate_topics(&topics).is_ok());
+    }
+
+    #[test]
+    fn test_load_topic_vocabulary() {
+        let contents = r#"
+{
+    "topics": [{"operation": "must_exist", "name": "bot"}],
+    "topic_vocabulary": {"allowed": ["bot", "twitch"], "remove_unknown": true}
+}"#;
+        let config = load_from_reader(std::io::Cursor::new(contents)).unwrap();
+        let vocabulary = config.topic_vocabulary.unwrap();
+
+        assert_eq!(
+            vocabulary.unknown(&topics(&["twitch", "discord", "bot", "chat"])),
+            vec!["chat", "discord"]
+        );
+
+        let contents = r#"
+{
+    "topics": [{"operation": "must_exist", "name": "irc"}],
+    "topic_vocabulary": {"allowed": ["bot", "twitch"]}
+}"#;
+        assert!(load_from_reader(std::io::Cursor::new(contents)).is_err());
+
+        let contents = r#"{"topics": [{"operation": "must_exist", "name": "Bot"}]}"#;
+        assert!(load_from_reader(std::io::Cursor::new(contents)).is_err());
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        let contents = r#"{"topics": [{"operation": "remove_matching", "regex": "("}]}"#;
+
+        assert!(load_from_reader(std::io::Cursor::new(contents)).is_err());
+    }
 }
@@ -0,0 +1,238 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Hook;
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_recent_deliveries() -> usize {
+    10
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentType {
+    #[default]
+    Json,
+    Form,
+}
+
+impl ContentType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentType::Json => "json",
+            ContentType::Form => "form",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Webhook {
+    /// The URL the payloads are delivered to. Hooks are matched by this URL
+    pub url: String,
+    #[serde(default)]
+    pub content_type: ContentType,
+    pub events: Vec<String>,
+    #[serde(default = "default_true")]
+    pub active: bool,
+    /// Only used when creating the hook, since GitHub never returns the secret
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Webhooks {
+    #[serde(default)]
+    pub hooks: Vec<Webhook>,
+    /// Report web hooks that are not listed in `hooks`, and delete them with --fix
+    #[serde(default)]
+    pub forbid_unknown: bool,
+    /// Report hooks where some of the most recent deliveries failed
+    #[serde(default)]
+    pub check_deliveries: bool,
+    #[serde(default = "default_recent_deliveries")]
+    pub recent_deliveries: usize,
+}
+
+impl Webhooks {
+    /// The recent deliveries are loaded with a single request, which returns at most 100 deliveries
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=100).contains(&self.recent_deliveries) {
+            bail!("recent_deliveries must be between 1 and 100");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum WebhookChange<'a> {
+    Create(&'a Webhook),
+    Update {
+        id: i64,
+        hook: &'a Webhook,
+        differences: Vec<String>,
+    },
+    Delete {
+        id: i64,
+        url: String,
+    },
+}
+
+impl Webhook {
+    fn differences(&self, actual: &Hook) -> Vec<String> {
+        let mut differences = Vec::new();
+
+        if actual.active != self.active {
+            differences.push(format!("active: {} -> {}", actual.active, self.active));
+        }
+
+        let actual_content_type = actual.config.content_type.as_deref().unwrap_or("form");
+        if actual_content_type != self.content_type.as_str() {
+            differences.push(format!(
+                "content_type: {actual_content_type} -> {}",
+                self.content_type.as_str()
+            ));
+        }
+
+        if actual.config.insecure_ssl() {
+            differences.push("insecure_ssl: enabled -> disabled".to_string());
+        }
+
+        let actual_events: HashSet<&String> = actual.events.iter().collect();
+        let desired_events: HashSet<&String> = self.events.iter().collect();
+        if actual_events != desired_events {
+            differences.push(format!(
+                "events: add({:?}), del({:?})",
+                desired_events.difference(&actual_events),
+                actual_events.difference(&desired_events),
+            ));
+        }
+
+        differences
+    }
+}
+
+impl Webhooks {
+    pub fn diff<'a>(&'a self, actual_hooks: &[Hook]) -> Vec<WebhookChange<'a>> {
+        let mut changes = Vec::new();
+
+        for hook in &self.hooks {
+            let actual = actual_hooks
+                .iter()
+                .find(|actual| actual.config.url.as_ref() == Some(&hook.url));
+
+            match actual {
+                Some(actual) => {
+                    let differences = hook.differences(actual);
+                    if !differences.is_empty() {
+                        changes.push(WebhookChange::Update {
+                            id: actual.id,
+                            hook,
+                            differences,
+                        });
+                    }
+                }
+                None => changes.push(WebhookChange::Create(hook)),
+            }
+        }
+
+        if self.forbid_unknown {
+            for actual in actual_hooks {
+                // Only web hooks have a URL we can match against
+                if actual.name != "web" {
+                    continue;
+                }
+
+                let url = actual.config.url.clone().unwrap_or_default();
+                if !self.hooks.iter().any(|hook| hook.url == url) {
+                    changes.push(WebhookChange::Delete { id: actual.id, url });
+                }
+            }
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::load_from_reader;
+    use crate::models::HookConfig;
+
+    use super::*;
+
+    fn hook(id: i64, url: &str, events: &[&str], insecure_ssl: &str) -> Hook {
+        Hook {
+            id,
+            name: "web".to_string(),
+            active: true,
+            events: events.iter().map(ToString::to_string).collect(),
+            config: HookConfig {
+                url: Some(url.to_string()),
+                content_type: Some("json".to_string()),
+                insecure_ssl: Some(serde_json::Value::String(insecure_ssl.to_string())),
+            },
+        }
+    }
+
+    #[test]
+    fn test_webhooks_diff() -> anyhow::Result<()> {
+        let contents = r#"
+{"webhooks": {
+    "hooks": [
+        {"url": "https://ci.example.com/hook", "events": ["push", "pull_request"]},
+        {"url": "https://chat.example.com/hook", "events": ["push"]},
+        {"url": "https://new.example.com/hook", "events": ["push"]}
+    ],
+    "forbid_unknown": true
+}}"#;
+        let config = load_from_reader(std::io::Cursor::new(contents))?;
+        let webhooks = config.webhooks.unwrap();
+
+        let actual = vec![
+            hook(
+                1,
+                "https://ci.example.com/hook",
+                &["pull_request", "push"],
+                "0",
+            ),
+            hook(2, "https://chat.example.com/hook", &["push"], "1"),
+            hook(3, "https://unknown.example.com/hook", &["push"], "0"),
+        ];
+
+        let changes = webhooks.diff(&actual);
+
+        assert_eq!(
+            changes,
+            vec![
+                WebhookChange::Update {
+                    id: 2,
+                    hook: &webhooks.hooks[1],
+                    differences: vec!["insecure_ssl: enabled -> disabled".to_string()],
+                },
+                WebhookChange::Create(&webhooks.hooks[2]),
+                WebhookChange::Delete {
+                    id: 3,
+                    url: "https://unknown.example.com/hook".to_string(),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recent_deliveries_out_of_range() {
+        for recent_deliveries in [0, 101] {
+            let contents =
+                format!(r#"{{"webhooks": {{"recent_deliveries": {recent_deliveries}}}}}"#);
+            let reader = std::io::Cursor::new(contents);
+
+            assert!(load_from_reader(reader).is_err());
+        }
+    }
+}